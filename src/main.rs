@@ -1,16 +1,22 @@
-use chrono::{DateTime, Local, NaiveDate, Timelike};
+use base64::Engine;
+use chrono::{DateTime, Datelike, Local, NaiveDate, Timelike, Weekday};
+use dashmap::DashMap;
 use poise::serenity_prelude as serenity;
+use rand::seq::SliceRandom;
+use regex::Regex;
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
+use std::collections::hash_map::Entry;
+use std::collections::{HashMap, HashSet, VecDeque};
 use std::fs;
-use std::sync::Arc;
-use std::time::Duration;
+use std::sync::atomic::{AtomicU32, AtomicU64, Ordering};
+use std::sync::{Arc, OnceLock};
+use std::time::{Duration, Instant};
 use tokio::sync::Mutex;
 use tokio::time;
 use poise::serenity_prelude::GatewayIntents;
 
 // Define the structure for standup entries
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 struct StandupEntry {
     user_id: String,
     display_name: String,
@@ -18,18 +24,405 @@ struct StandupEntry {
     plan: String,
     blockers: String,
     timestamp: DateTime<Local>,
+    #[serde(default)]
+    attachments: Vec<String>, // https:// links to related PRs, docs or issues
+    #[serde(default)]
+    blocker_severity: Option<BlockerSeverity>, // Set from the blocker keywords when the entry is submitted
+}
+
+// Entries are ordered by submission time, then by user ID so equal timestamps sort stably.
+// The remaining fields only break ties to keep the ordering consistent with `Eq`.
+impl Ord for StandupEntry {
+    fn cmp(&self, other: &Self) -> std::cmp::Ordering {
+        self.timestamp
+            .cmp(&other.timestamp)
+            .then_with(|| self.user_id.cmp(&other.user_id))
+            .then_with(|| self.display_name.cmp(&other.display_name))
+            .then_with(|| self.did.cmp(&other.did))
+            .then_with(|| self.plan.cmp(&other.plan))
+            .then_with(|| self.blockers.cmp(&other.blockers))
+            .then_with(|| self.attachments.cmp(&other.attachments))
+            .then_with(|| self.blocker_severity.cmp(&other.blocker_severity))
+    }
+}
+
+impl PartialOrd for StandupEntry {
+    fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+// Settings read from the environment at startup
+#[derive(Debug, Clone)]
+struct BotConfig {
+    discord_token: String,
+    application_id: Option<u64>, // Needed to build the OAuth2 invite URL
+    storage_format: StorageFormat, // From STORAGE_FORMAT; how bot data is written to disk
+}
+
+impl BotConfig {
+    fn from_env() -> Self {
+        let discord_token = std::env::var("DISCORD_TOKEN").expect("Missing DISCORD_TOKEN");
+
+        let application_id = match std::env::var("APPLICATION_ID") {
+            Ok(value) => match value.trim().parse::<u64>() {
+                Ok(id) => Some(id),
+                Err(_) => {
+                    eprintln!("APPLICATION_ID is not a valid application ID: {}", value);
+                    None
+                }
+            },
+            Err(_) => None,
+        };
+
+        let storage_format = match std::env::var("STORAGE_FORMAT") {
+            Ok(value) if value.trim().eq_ignore_ascii_case("msgpack") => StorageFormat::MsgPack,
+            Ok(value) if value.trim().eq_ignore_ascii_case("json") => StorageFormat::Json,
+            Ok(value) => {
+                eprintln!("STORAGE_FORMAT must be 'json' or 'msgpack', not '{}'. Using json.", value);
+                StorageFormat::Json
+            }
+            Err(_) => StorageFormat::Json,
+        };
+
+        BotConfig {
+            discord_token,
+            application_id,
+            storage_format,
+        }
+    }
+}
+
+// File format used for the saved bot data
+#[derive(Debug, Clone, Copy, PartialEq, Eq, poise::ChoiceParameter)]
+enum StorageFormat {
+    #[name = "json"]
+    Json,
+    #[name = "msgpack"]
+    MsgPack,
+}
+
+impl StorageFormat {
+    fn path(&self) -> &'static str {
+        match self {
+            StorageFormat::Json => "bot_data.json",
+            StorageFormat::MsgPack => "bot_data.msgpack",
+        }
+    }
+
+    fn other(&self) -> StorageFormat {
+        match self {
+            StorageFormat::Json => StorageFormat::MsgPack,
+            StorageFormat::MsgPack => StorageFormat::Json,
+        }
+    }
+}
+
+// A plan saved ahead of time to fill in the user's next standup
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct TomorrowPlan {
+    plan: String,
+    saved_on: NaiveDate,
+}
+
+// Per-user settings changed with /my_config
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+struct UserPrefs {
+    #[serde(default)]
+    dm_confirmation: bool,
+}
+
+// A user opting out of standup for a day
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct SkipEntry {
+    user_id: String,
+    display_name: String,
+    date: NaiveDate,
+    reason: Option<String>,
+    approved: bool,
+}
+
+// A poll letting the team choose the summary time
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct VotePoll {
+    channel_id: serenity::ChannelId,
+    message_id: serenity::MessageId,
+    options: Vec<(u32, u32)>, // (hour, minute) in 24-hour format
+    votes: HashMap<String, usize>, // User ID -> index into `options`
+    ends_at: DateTime<Local>,
+    auto_apply: bool, // Whether the winning time becomes the summary time
+    started_by: String,
+}
+
+// What kind of message the bot posted, for the message log
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+enum MessageType {
+    Summary,
+    Announcement,
+    LiveFeed,
+    Poll,
+    SetupHint,
+    Digest,
+    DmConfirmation,
+}
+
+// A message the bot posted; entry_count is the number of standups it covered, if any
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct MessageLogEntry {
+    message_id: serenity::MessageId,
+    channel_id: serenity::ChannelId,
+    message_type: MessageType,
+    timestamp: DateTime<Local>,
+    entry_count: usize,
+}
+
+// Which days the standup summary is sent on; the time of day is kept in `summary_time`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+enum SummarySchedule {
+    Daily,
+    Weekly { day: Weekday },
+    BiWeekly { day: Weekday, week: BiWeeklyParity },
+}
+
+// Which ISO weeks a bi-weekly summary is sent in. Years with 53 ISO weeks end on an odd
+// week followed by week 1, so odd schedules fire two weeks running at the turn of those years.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+enum BiWeeklyParity {
+    Even,
+    Odd,
+}
+
+impl BiWeeklyParity {
+    fn of(date: NaiveDate) -> Self {
+        if date.iso_week().week().is_multiple_of(2) {
+            BiWeeklyParity::Even
+        } else {
+            BiWeeklyParity::Odd
+        }
+    }
+
+    fn name(&self) -> &'static str {
+        match self {
+            BiWeeklyParity::Even => "even",
+            BiWeeklyParity::Odd => "odd",
+        }
+    }
+}
+
+impl SummarySchedule {
+    // Whether a summary is due on the given date
+    fn fires_on(&self, date: NaiveDate) -> bool {
+        match self {
+            SummarySchedule::Daily => true,
+            SummarySchedule::Weekly { day } => date.weekday() == *day,
+            SummarySchedule::BiWeekly { day, week } => date.weekday() == *day && BiWeeklyParity::of(date) == *week,
+        }
+    }
+
+    // The next time a summary is due at or after `now`. A 53-week ISO year puts two odd weeks
+    // in a row, so a bi-weekly schedule can go three weeks between summaries.
+    fn next_fire(&self, now: DateTime<Local>, (hour, minute): (u32, u32)) -> Option<DateTime<Local>> {
+        (0..=21)
+            .filter_map(|offset| now.date_naive().checked_add_days(chrono::Days::new(offset)))
+            .filter(|date| self.fires_on(*date))
+            .filter_map(|date| date.and_hms_opt(hour, minute, 0))
+            .filter_map(|naive| naive.and_local_timezone(Local).earliest())
+            .find(|fire| *fire >= now)
+    }
+
+    // Human-readable description of when summaries are sent
+    fn describe(&self, (hour, minute): (u32, u32)) -> String {
+        match self {
+            SummarySchedule::Daily => format!("Daily at {:02}:{:02}", hour, minute),
+            SummarySchedule::Weekly { day } => format!("Every {} at {:02}:{:02}", weekday_name(*day), hour, minute),
+            SummarySchedule::BiWeekly { day, week } => format!(
+                "Every other {} ({} ISO weeks) at {:02}:{:02}",
+                weekday_name(*day),
+                week.name(),
+                hour,
+                minute
+            ),
+        }
+    }
+
+    // First day of the summary period that ends on `today`
+    fn period_start(&self, today: NaiveDate) -> NaiveDate {
+        match self {
+            SummarySchedule::Daily => today,
+            SummarySchedule::Weekly { .. } => today - chrono::Duration::days(6),
+            SummarySchedule::BiWeekly { .. } => today - chrono::Duration::days(13),
+        }
+    }
+
+    // Word used to describe the summary period, e.g. in the summary heading
+    fn period_name(&self) -> &'static str {
+        match self {
+            SummarySchedule::Daily => "Daily",
+            SummarySchedule::Weekly { .. } => "Weekly",
+            SummarySchedule::BiWeekly { .. } => "Bi-weekly",
+        }
+    }
+}
+
+// Full English name of a weekday
+fn weekday_name(day: Weekday) -> &'static str {
+    match day {
+        Weekday::Mon => "Monday",
+        Weekday::Tue => "Tuesday",
+        Weekday::Wed => "Wednesday",
+        Weekday::Thu => "Thursday",
+        Weekday::Fri => "Friday",
+        Weekday::Sat => "Saturday",
+        Weekday::Sun => "Sunday",
+    }
+}
+
+// How serious a reported blocker is, detected from keywords in the answer
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize, poise::ChoiceParameter)]
+enum BlockerSeverity {
+    Low,
+    Medium,
+    High,
+    Critical,
+}
+
+impl BlockerSeverity {
+    fn icon(&self) -> &'static str {
+        match self {
+            BlockerSeverity::Low => "🟢",
+            BlockerSeverity::Medium => "🟡",
+            BlockerSeverity::High => "🟠",
+            BlockerSeverity::Critical => "🔴",
+        }
+    }
+}
+
+// How users are ordered in the summary
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize, poise::ChoiceParameter)]
+enum SortOrder {
+    // Team grouping was offered before the bot had teams and always came out alphabetical
+    #[name = "Alphabetical"]
+    #[serde(alias = "TeamGrouped")]
+    Alphabetical,
+    #[default]
+    #[name = "Submission time"]
+    SubmissionTime,
+    #[name = "Random"]
+    Random,
 }
 
 // Define our bot's state
 #[derive(Clone)]
 struct Data {
+    config: Arc<BotConfig>,
     standup_entries: Arc<Mutex<Vec<StandupEntry>>>,
     summary_channel_id: Arc<Mutex<Option<serenity::ChannelId>>>,
     summary_time: Arc<Mutex<(u32, u32)>>, // (hour, minute) in 24-hour format
     last_summary_date: Arc<Mutex<Option<NaiveDate>>>, // Using NaiveDate instead of deprecated Date<Local>
+    setup_hint_sent: Arc<Mutex<HashSet<serenity::GuildId>>>, // Guilds already told how to configure the bot
+    entry_format: Arc<Mutex<Option<String>>>, // Custom template for each user's summary section
+    last_perm_check: Arc<Mutex<Option<NaiveDate>>>, // Date of the last automatic permission check
+    perm_check_enabled: Arc<Mutex<bool>>, // Whether the weekly permission check runs
+    summary_schedule: Arc<Mutex<SummarySchedule>>, // Which days the summary is sent on
+    summary_sort_order: Arc<Mutex<SortOrder>>, // How users are ordered in the summary
+    last_avatar_change: Arc<Mutex<Option<DateTime<Local>>>>, // When /set_bot_avatar last changed the avatar
+    live_feed_channel_id: Arc<Mutex<Option<serenity::ChannelId>>>, // Private channel notified of each submission
+    rate_limit_hits: Arc<AtomicU64>, // Rate limit (HTTP 429) errors seen while sending
+    tomorrow_plans: Arc<DashMap<String, TomorrowPlan>>, // Plans saved with /plan_for_tomorrow, keyed by user ID
+    trivial_blocker_phrases: Arc<Mutex<HashSet<String>>>, // Blocker answers that mean "no blockers"
+    skip_log: Arc<Mutex<Vec<SkipEntry>>>, // Days users chose to skip with /skip_today
+    require_skip_approval: Arc<Mutex<bool>>, // Whether skips need /approve_skip before they count
+    shard_id: Arc<AtomicU32>, // Shard this process runs, set when the bot connects
+    total_shards: Arc<AtomicU32>, // Total number of shards the bot runs with
+    summary_footer: Arc<Mutex<Option<String>>>, // Custom line added to the end of every summary
+    active_vote: Arc<Mutex<Option<VotePoll>>>, // The running /vote_on_standup_time poll, if any
+    auto_update_topic: Arc<Mutex<bool>>, // Whether the summary channel topic shows the standup status
+    last_topic_update: Arc<Mutex<Option<DateTime<Local>>>>, // When the channel topic was last changed, for debouncing
+    user_prefs: Arc<DashMap<String, UserPrefs>>, // Settings from /my_config, keyed by user ID
+    announcement_channel_id: Arc<Mutex<Option<serenity::ChannelId>>>, // Channel for the bot's operational messages; falls back to the summary channel
+    pending_entry_expiry_hours: Arc<Mutex<u32>>, // Pending entries older than this are dropped instead of summarized; 0 keeps them forever
+    blocker_keywords: Arc<Mutex<HashMap<BlockerSeverity, Vec<String>>>>, // Words in a blockers answer that mark its severity
+    message_log: Arc<Mutex<VecDeque<MessageLogEntry>>>, // Messages the bot has posted, newest last, for /channel_report
+    summary_image_url: Arc<Mutex<Option<String>>>, // Image shown below every summary; may contain {date}
+    notification_cooldown_hours: Arc<Mutex<u32>>, // Minimum hours between two notifications of the same type
+    last_notification_timestamps: Arc<Mutex<HashMap<NotificationType, DateTime<Local>>>>, // When each type of notification was last posted, for the cooldown
+    scheduler_restarts: Arc<AtomicU32>, // How often the scheduler has been restarted after crashing
+    digest_channel_id: Arc<Mutex<Option<serenity::ChannelId>>>, // Channel with a running tally of pending standups
+    digest_message_id: Arc<Mutex<Option<serenity::MessageId>>>, // The tally message in the digest channel that is edited in place
+}
+
+impl Data {
+    // Whether a guild is assigned to this shard, using Discord's shard formula
+    fn handles_guild(&self, guild_id: serenity::GuildId) -> bool {
+        let shard_id = self.shard_id.load(Ordering::Relaxed) as u64;
+        let total_shards = self.total_shards.load(Ordering::Relaxed).max(1) as u64;
+        (guild_id.0 >> 22) % total_shards == shard_id
+    }
+}
+
+// Errors returned by commands and background tasks
+#[derive(Debug)]
+enum BotError {
+    UserError(String),        // Invalid input; the message is shown to the user
+    PermissionDenied(String), // The user isn't allowed to do this; the message is shown to the user
+    DiscordApi(serenity::Error),
+    StorageError(std::io::Error),
+    SerdeError(serde_json::Error),
+    MsgPackError(rmp_serde::encode::Error),
+    Internal(String),
+}
+
+impl std::fmt::Display for BotError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            BotError::UserError(message) => write!(f, "{}", message),
+            BotError::PermissionDenied(message) => write!(f, "{}", message),
+            BotError::DiscordApi(e) => write!(f, "Discord API error: {}", e),
+            BotError::StorageError(e) => write!(f, "Storage error: {}", e),
+            BotError::SerdeError(e) => write!(f, "Serialization error: {}", e),
+            BotError::MsgPackError(e) => write!(f, "MessagePack serialization error: {}", e),
+            BotError::Internal(message) => write!(f, "Internal error: {}", message),
+        }
+    }
+}
+
+impl std::error::Error for BotError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            BotError::DiscordApi(e) => Some(e),
+            BotError::StorageError(e) => Some(e),
+            BotError::SerdeError(e) => Some(e),
+            BotError::MsgPackError(e) => Some(e),
+            _ => None,
+        }
+    }
+}
+
+impl From<serenity::Error> for BotError {
+    fn from(e: serenity::Error) -> Self {
+        BotError::DiscordApi(e)
+    }
+}
+
+impl From<std::io::Error> for BotError {
+    fn from(e: std::io::Error) -> Self {
+        BotError::StorageError(e)
+    }
 }
 
-type Error = Box<dyn std::error::Error + Send + Sync>;
+impl From<serde_json::Error> for BotError {
+    fn from(e: serde_json::Error) -> Self {
+        BotError::SerdeError(e)
+    }
+}
+
+impl From<rmp_serde::encode::Error> for BotError {
+    fn from(e: rmp_serde::encode::Error) -> Self {
+        BotError::MsgPackError(e)
+    }
+}
+
+type Error = BotError;
 type Context<'a> = poise::Context<'a, Data, Error>;
 
 #[tokio::main]
@@ -37,8 +430,8 @@ async fn main() {
     // Load environment variables from .env file
     dotenv::dotenv().ok();
 
-    // Get the Discord token from environment variables
-    let token = std::env::var("DISCORD_TOKEN").expect("Missing DISCORD_TOKEN");
+    // Read the bot configuration from environment variables
+    let config = BotConfig::from_env();
 
     let intents = GatewayIntents::GUILDS
         | GatewayIntents::GUILD_MESSAGES
@@ -47,23 +440,88 @@ async fn main() {
     // Create the framework
     let framework = poise::Framework::builder()
         .options(poise::FrameworkOptions {
-            commands: vec![standup(), set_summary_channel(), set_summary_time(), trigger_summary()],
+            commands: vec![
+                standup(),
+                set_summary_channel(),
+                set_summary_time(),
+                trigger_summary(),
+                generate_invite(),
+                set_entry_format(),
+                blockers_report(),
+                verify_permissions(),
+                toggle_perm_check(),
+                set_schedule(),
+                set_summary_sort_order(),
+                set_bot_avatar(),
+                set_live_feed_channel(),
+                clear_live_feed_channel(),
+                benchmark(),
+                status(),
+                plan_for_tomorrow(),
+                clear_tomorrow_plan(),
+                add_trivial_phrase(),
+                remove_trivial_phrase(),
+                list_trivial_phrases(),
+                skip_today(),
+                approve_skip(),
+                toggle_skip_approval(),
+                set_summary_footer(),
+                clear_summary_footer(),
+                vote_on_standup_time(),
+                toggle_auto_topic(),
+                onboarding(),
+                my_config(),
+                current_week_parity(),
+                standup_help(),
+                view_attachments(),
+                show_queue(),
+                convert_storage(),
+                confirm_storage_migration(),
+                set_announcement_channel(),
+                clear_announcement_channel(),
+                set_entry_expiry(),
+                add_blocker_keyword(),
+                remove_blocker_keyword(),
+                list_blocker_keywords(),
+                channel_report(),
+                set_summary_image(),
+                clear_summary_image(),
+                set_notification_cooldown(),
+                set_digest_channel(),
+                clear_digest_channel(),
+            ],
+            on_error: |error| Box::pin(on_error(error)),
+            event_handler: |ctx, event, _framework, data| Box::pin(event_handler(ctx, event, data)),
             ..Default::default()
         })
-        .token(token)
+        .token(config.discord_token.clone())
         .intents(intents)
-        .setup(|ctx, _ready, framework| {
+        .setup(|ctx, ready, framework| {
             Box::pin(async move {
                 poise::builtins::register_globally(ctx, &framework.options().commands).await?;
                 
                 // Load any saved data
-                let data = load_data().await;
+                let data = load_data(config).await;
+                
+                // Remember which shard this is so we only post to guilds it handles
+                if let Some([shard_id, total_shards]) = ready.shard {
+                    data.shard_id.store(shard_id as u32, Ordering::Relaxed);
+                    data.total_shards.store(total_shards.max(1) as u32, Ordering::Relaxed);
+                }
                 
-                // Start the scheduled task for sending summary
+                // Start the scheduled task for sending summary, restarting it if it crashes
                 let ctx_clone = ctx.clone();
                 let data_clone = data.clone();
                 tokio::spawn(async move {
-                    schedule_summary_task(ctx_clone, data_clone).await;
+                    supervise_scheduler(ctx_clone, data_clone).await;
+                });
+                
+                // Point unconfigured guilds at a likely standup channel
+                let ctx_clone = ctx.clone();
+                let data_clone = data.clone();
+                let guild_ids: Vec<serenity::GuildId> = ready.guilds.iter().map(|g| g.id).collect();
+                tokio::spawn(async move {
+                    send_setup_hints(&ctx_clone, &data_clone, guild_ids).await;
                 });
                 
                 println!("Bot successfully started!");
@@ -75,16 +533,103 @@ async fn main() {
     framework.run().await.unwrap();
 }
 
+// Reply to command errors based on what went wrong
+async fn on_error(error: poise::FrameworkError<'_, Data, Error>) {
+    match error {
+        poise::FrameworkError::Command { error, ctx } => {
+            let reply = match &error {
+                // These are meant for the user, so show them as-is
+                BotError::UserError(message) | BotError::PermissionDenied(message) => message.clone(),
+                _ => {
+                    eprintln!("Error in command /{}: {}", ctx.command().name, error);
+                    "Something went wrong while running this command. Please try again later.".to_string()
+                }
+            };
+            if let Err(e) = ctx.say(reply).await {
+                eprintln!("Failed to report command error: {:?}", e);
+            }
+        }
+        other => {
+            if let Err(e) = poise::builtins::on_error(other).await {
+                eprintln!("Error while handling error: {:?}", e);
+            }
+        }
+    }
+}
+
+// Handle Discord events that aren't commands
+async fn event_handler(ctx: &serenity::Context, event: &poise::Event<'_>, data: &Data) -> Result<(), Error> {
+    if let poise::Event::InteractionCreate {
+        interaction: serenity::Interaction::MessageComponent(component),
+    } = event
+    {
+        if component.data.custom_id.starts_with(VOTE_BUTTON_PREFIX) {
+            handle_vote_button(ctx, data, component).await?;
+        }
+    }
+
+    Ok(())
+}
+
 // Load saved data from disk or create default data
-async fn load_data() -> Data {
-    if let Ok(file) = fs::read_to_string("bot_data.json") {
-        if let Ok(saved) = serde_json::from_str::<SavedData>(&file) {
-            return Data {
+async fn load_data(config: BotConfig) -> Data {
+    let config = Arc::new(config);
+
+    // Prefer the configured format, falling back to the other one so switching formats migrates the data
+    for format in [config.storage_format, config.storage_format.other()] {
+        if let Some(saved) = read_saved_data(format) {
+            if format != config.storage_format {
+                println!("Loaded data from {}; it will be saved to {} from now on", format.path(), config.storage_format.path());
+            }
+            let data = Data {
+                config,
                 standup_entries: Arc::new(Mutex::new(saved.standup_entries)),
                 summary_channel_id: Arc::new(Mutex::new(saved.summary_channel_id)),
                 summary_time: Arc::new(Mutex::new(saved.summary_time.unwrap_or((17, 0)))), // Default 5:00 PM
                 last_summary_date: Arc::new(Mutex::new(saved.last_summary_date)),
+                setup_hint_sent: Arc::new(Mutex::new(saved.setup_hint_sent)),
+                entry_format: Arc::new(Mutex::new(saved.entry_format)),
+                last_perm_check: Arc::new(Mutex::new(saved.last_perm_check)),
+                perm_check_enabled: Arc::new(Mutex::new(saved.perm_check_enabled.unwrap_or(true))),
+                summary_schedule: Arc::new(Mutex::new(saved.summary_schedule.unwrap_or(SummarySchedule::Daily))),
+                summary_sort_order: Arc::new(Mutex::new(saved.summary_sort_order.unwrap_or_default())),
+                last_avatar_change: Arc::new(Mutex::new(saved.last_avatar_change)),
+                live_feed_channel_id: Arc::new(Mutex::new(saved.live_feed_channel_id)),
+                rate_limit_hits: Arc::new(AtomicU64::new(0)),
+                tomorrow_plans: Arc::new(saved.tomorrow_plans.into_iter().collect()),
+                trivial_blocker_phrases: Arc::new(Mutex::new(saved.trivial_blocker_phrases.unwrap_or_else(default_trivial_blocker_phrases))),
+                skip_log: Arc::new(Mutex::new(saved.skip_log)),
+                require_skip_approval: Arc::new(Mutex::new(saved.require_skip_approval.unwrap_or(false))),
+                shard_id: Arc::new(AtomicU32::new(0)),
+                total_shards: Arc::new(AtomicU32::new(1)),
+                summary_footer: Arc::new(Mutex::new(saved.summary_footer)),
+                active_vote: Arc::new(Mutex::new(saved.active_vote)),
+                auto_update_topic: Arc::new(Mutex::new(saved.auto_update_topic.unwrap_or(false))),
+                last_topic_update: Arc::new(Mutex::new(None)),
+                user_prefs: Arc::new(saved.user_prefs.into_iter().collect()),
+                announcement_channel_id: Arc::new(Mutex::new(saved.announcement_channel_id)),
+                pending_entry_expiry_hours: Arc::new(Mutex::new(saved.pending_entry_expiry_hours.unwrap_or(DEFAULT_ENTRY_EXPIRY_HOURS))),
+                blocker_keywords: Arc::new(Mutex::new(saved.blocker_keywords.unwrap_or_else(default_blocker_keywords))),
+                message_log: Arc::new(Mutex::new(saved.message_log)),
+                summary_image_url: Arc::new(Mutex::new(saved.summary_image_url)),
+                notification_cooldown_hours: Arc::new(Mutex::new(saved.notification_cooldown_hours.unwrap_or(DEFAULT_NOTIFICATION_COOLDOWN_HOURS))),
+                last_notification_timestamps: Arc::new(Mutex::new(HashMap::new())),
+                scheduler_restarts: Arc::new(AtomicU32::new(0)),
+                digest_channel_id: Arc::new(Mutex::new(saved.digest_channel_id)),
+                digest_message_id: Arc::new(Mutex::new(saved.digest_message_id)),
             };
+            
+            // Report anything in the saved file that doesn't make sense
+            for warning in validate_loaded_data(&data).await {
+                if warning.critical {
+                    eprintln!("Invalid saved data in {}: {}. Resetting it to the default.", warning.field, warning.message);
+                    reset_to_default(&data, &warning.field).await;
+                } else {
+                    println!("Warning about saved data in {}: {}", warning.field, warning.message);
+                }
+            }
+            
+            return data;
         }
     }
     
@@ -92,10 +637,111 @@ async fn load_data() -> Data {
     
     // Default data if nothing is loaded
     Data {
+        config,
         standup_entries: Arc::new(Mutex::new(Vec::new())),
         summary_channel_id: Arc::new(Mutex::new(None)),
         summary_time: Arc::new(Mutex::new((17, 0))), // Default 5:00 PM
         last_summary_date: Arc::new(Mutex::new(None)),
+        setup_hint_sent: Arc::new(Mutex::new(HashSet::new())),
+        entry_format: Arc::new(Mutex::new(None)),
+        last_perm_check: Arc::new(Mutex::new(None)),
+        perm_check_enabled: Arc::new(Mutex::new(true)),
+        summary_schedule: Arc::new(Mutex::new(SummarySchedule::Daily)),
+        summary_sort_order: Arc::new(Mutex::new(SortOrder::default())),
+        last_avatar_change: Arc::new(Mutex::new(None)),
+        live_feed_channel_id: Arc::new(Mutex::new(None)),
+        rate_limit_hits: Arc::new(AtomicU64::new(0)),
+        tomorrow_plans: Arc::new(DashMap::new()),
+        trivial_blocker_phrases: Arc::new(Mutex::new(default_trivial_blocker_phrases())),
+        skip_log: Arc::new(Mutex::new(Vec::new())),
+        require_skip_approval: Arc::new(Mutex::new(false)),
+        shard_id: Arc::new(AtomicU32::new(0)),
+        total_shards: Arc::new(AtomicU32::new(1)),
+        summary_footer: Arc::new(Mutex::new(None)),
+        active_vote: Arc::new(Mutex::new(None)),
+        auto_update_topic: Arc::new(Mutex::new(false)),
+        last_topic_update: Arc::new(Mutex::new(None)),
+        user_prefs: Arc::new(DashMap::new()),
+        announcement_channel_id: Arc::new(Mutex::new(None)),
+        pending_entry_expiry_hours: Arc::new(Mutex::new(DEFAULT_ENTRY_EXPIRY_HOURS)),
+        blocker_keywords: Arc::new(Mutex::new(default_blocker_keywords())),
+        message_log: Arc::new(Mutex::new(VecDeque::new())),
+        summary_image_url: Arc::new(Mutex::new(None)),
+        notification_cooldown_hours: Arc::new(Mutex::new(DEFAULT_NOTIFICATION_COOLDOWN_HOURS)),
+        last_notification_timestamps: Arc::new(Mutex::new(HashMap::new())),
+        scheduler_restarts: Arc::new(AtomicU32::new(0)),
+        digest_channel_id: Arc::new(Mutex::new(None)),
+        digest_message_id: Arc::new(Mutex::new(None)),
+    }
+}
+
+// A problem found in the saved data when loading it
+#[derive(Debug)]
+struct DataWarning {
+    field: String,
+    message: String,
+    critical: bool, // Critical problems are reset to the default value
+}
+
+impl DataWarning {
+    fn new(field: &str, message: impl Into<String>, critical: bool) -> Self {
+        DataWarning {
+            field: field.to_string(),
+            message: message.into(),
+            critical,
+        }
+    }
+}
+
+// Check loaded data for values the bot can't work with
+async fn validate_loaded_data(data: &Data) -> Vec<DataWarning> {
+    let mut warnings = Vec::new();
+
+    let (hour, minute) = *data.summary_time.lock().await;
+    if hour > 23 || minute > 59 {
+        warnings.push(DataWarning::new(
+            "summary_time",
+            format!("{:02}:{:02} is not a valid time of day", hour, minute),
+            true,
+        ));
+    }
+
+    if let Some(template) = data.entry_format.lock().await.as_ref() {
+        let missing = missing_entry_variables(template);
+        if !missing.is_empty() {
+            warnings.push(DataWarning::new(
+                "entry_format",
+                format!("template is missing {}", missing.join(", ")),
+                true,
+            ));
+        }
+    }
+
+    let now = Local::now();
+    let future_entries = data
+        .standup_entries
+        .lock()
+        .await
+        .iter()
+        .filter(|e| e.timestamp > now)
+        .count();
+    if future_entries > 0 {
+        warnings.push(DataWarning::new(
+            "standup_entries",
+            format!("{} entries have a timestamp in the future", future_entries),
+            false,
+        ));
+    }
+
+    warnings
+}
+
+// Reset a field that failed validation to its default value
+async fn reset_to_default(data: &Data, field: &str) {
+    match field {
+        "summary_time" => *data.summary_time.lock().await = (17, 0), // Default 5:00 PM
+        "entry_format" => *data.entry_format.lock().await = None,
+        _ => eprintln!("No default to reset {} to", field),
     }
 }
 
@@ -105,319 +751,3848 @@ struct SavedData {
     summary_channel_id: Option<serenity::ChannelId>,
     summary_time: Option<(u32, u32)>,
     last_summary_date: Option<NaiveDate>, // Using NaiveDate which is serializable
+    #[serde(default)]
+    setup_hint_sent: HashSet<serenity::GuildId>,
+    entry_format: Option<String>,
+    last_perm_check: Option<NaiveDate>,
+    perm_check_enabled: Option<bool>,
+    summary_schedule: Option<SummarySchedule>,
+    summary_sort_order: Option<SortOrder>,
+    last_avatar_change: Option<DateTime<Local>>,
+    live_feed_channel_id: Option<serenity::ChannelId>,
+    #[serde(default)]
+    tomorrow_plans: HashMap<String, TomorrowPlan>,
+    trivial_blocker_phrases: Option<HashSet<String>>,
+    #[serde(default)]
+    skip_log: Vec<SkipEntry>,
+    require_skip_approval: Option<bool>,
+    summary_footer: Option<String>,
+    active_vote: Option<VotePoll>,
+    auto_update_topic: Option<bool>,
+    #[serde(default)]
+    user_prefs: HashMap<String, UserPrefs>,
+    announcement_channel_id: Option<serenity::ChannelId>,
+    pending_entry_expiry_hours: Option<u32>,
+    blocker_keywords: Option<HashMap<BlockerSeverity, Vec<String>>>,
+    #[serde(default)]
+    message_log: VecDeque<MessageLogEntry>,
+    summary_image_url: Option<String>,
+    notification_cooldown_hours: Option<u32>,
+    digest_channel_id: Option<serenity::ChannelId>,
+    digest_message_id: Option<serenity::MessageId>,
+}
+
+// Read and decode a saved data file, if it exists and is valid
+fn read_saved_data(format: StorageFormat) -> Option<SavedData> {
+    let bytes = fs::read(format.path()).ok()?;
+    let decoded = match format {
+        StorageFormat::Json => serde_json::from_slice::<SavedData>(&bytes).map_err(|e| e.to_string()),
+        StorageFormat::MsgPack => rmp_serde::from_slice::<SavedData>(&bytes).map_err(|e| e.to_string()),
+    };
+
+    match decoded {
+        Ok(saved) => Some(saved),
+        Err(e) => {
+            eprintln!("Could not read {}: {}", format.path(), e);
+            None
+        }
+    }
+}
+
+#[allow(clippy::result_large_err)] // BotError carries serenity::Error, which is large
+fn encode_saved_data(saved_data: &SavedData, format: StorageFormat) -> Result<Vec<u8>, Error> {
+    match format {
+        StorageFormat::Json => Ok(serde_json::to_vec_pretty(saved_data)?),
+        // Named fields keep #[serde(default)] and new Option fields working when loading older files
+        StorageFormat::MsgPack => Ok(rmp_serde::to_vec_named(saved_data)?),
+    }
 }
 
 // Save data to disk
 async fn save_data(data: &Data) -> Result<(), Error> {
+    let saved_data = saved_data_snapshot(data).await;
+    let format = data.config.storage_format;
+    
+    fs::write(format.path(), encode_saved_data(&saved_data, format)?)?;
+    
+    // Both files exist while a /convert_storage migration is pending. Keep the other one up to date
+    // too, so whichever format the bot restarts with has the latest data.
+    let other = format.other();
+    if std::path::Path::new(other.path()).exists() {
+        fs::write(other.path(), encode_saved_data(&saved_data, other)?)?;
+    }
+    
+    println!("Data saved successfully");
+    Ok(())
+}
+
+// Copy the persisted parts of the live data
+async fn saved_data_snapshot(data: &Data) -> SavedData {
     let entries = data.standup_entries.lock().await.clone();
     let channel_id = *data.summary_channel_id.lock().await;
     let summary_time = *data.summary_time.lock().await;
     let last_summary_date = *data.last_summary_date.lock().await;
+    let setup_hint_sent = data.setup_hint_sent.lock().await.clone();
+    let entry_format = data.entry_format.lock().await.clone();
+    let last_perm_check = *data.last_perm_check.lock().await;
+    let perm_check_enabled = Some(*data.perm_check_enabled.lock().await);
+    let summary_schedule = Some(*data.summary_schedule.lock().await);
+    let summary_sort_order = Some(*data.summary_sort_order.lock().await);
+    let last_avatar_change = *data.last_avatar_change.lock().await;
+    let live_feed_channel_id = *data.live_feed_channel_id.lock().await;
+    let tomorrow_plans = data
+        .tomorrow_plans
+        .iter()
+        .map(|plan| (plan.key().clone(), plan.value().clone()))
+        .collect();
+    let trivial_blocker_phrases = Some(data.trivial_blocker_phrases.lock().await.clone());
+    let skip_log = data.skip_log.lock().await.clone();
+    let require_skip_approval = Some(*data.require_skip_approval.lock().await);
+    let summary_footer = data.summary_footer.lock().await.clone();
+    let active_vote = data.active_vote.lock().await.clone();
+    let auto_update_topic = Some(*data.auto_update_topic.lock().await);
+    let user_prefs = data
+        .user_prefs
+        .iter()
+        .map(|prefs| (prefs.key().clone(), prefs.value().clone()))
+        .collect();
+    let announcement_channel_id = *data.announcement_channel_id.lock().await;
+    let pending_entry_expiry_hours = Some(*data.pending_entry_expiry_hours.lock().await);
+    let blocker_keywords = Some(data.blocker_keywords.lock().await.clone());
+    let message_log = data.message_log.lock().await.clone();
+    let summary_image_url = data.summary_image_url.lock().await.clone();
+    let notification_cooldown_hours = Some(*data.notification_cooldown_hours.lock().await);
+    let digest_channel_id = *data.digest_channel_id.lock().await;
+    let digest_message_id = *data.digest_message_id.lock().await;
     
-    let saved_data = SavedData {
+    SavedData {
         standup_entries: entries,
         summary_channel_id: channel_id,
         summary_time: Some(summary_time),
         last_summary_date,
+        setup_hint_sent,
+        entry_format,
+        last_perm_check,
+        perm_check_enabled,
+        summary_schedule,
+        summary_sort_order,
+        last_avatar_change,
+        live_feed_channel_id,
+        tomorrow_plans,
+        trivial_blocker_phrases,
+        skip_log,
+        require_skip_approval,
+        summary_footer,
+        active_vote,
+        auto_update_topic,
+        user_prefs,
+        announcement_channel_id,
+        pending_entry_expiry_hours,
+        blocker_keywords,
+        message_log,
+        summary_image_url,
+        notification_cooldown_hours,
+        digest_channel_id,
+        digest_message_id,
+    }
+}
+
+// Channel names that suggest a channel is meant for standups, in order of preference
+const STANDUP_CHANNEL_NAMES: [&str; 4] = ["standup", "standups", "daily-standup", "agilemate"];
+
+// Find a text channel in the guild whose name looks like a standup channel
+async fn find_standup_channel(ctx: &serenity::Context, guild_id: serenity::GuildId) -> Option<serenity::GuildChannel> {
+    let channels = match guild_id.channels(ctx).await {
+        Ok(channels) => channels,
+        Err(e) => {
+            eprintln!("Failed to fetch channels for guild {}: {:?}", guild_id, e);
+            return None;
+        }
     };
-    
-    let json = serde_json::to_string_pretty(&saved_data)
-        .map_err(|e| format!("Failed to serialize data: {}", e))?;
-    
-    fs::write("bot_data.json", json)
-        .map_err(|e| format!("Failed to write data file: {}", e))?;
-    
-    println!("Data saved successfully");
-    Ok(())
+
+    STANDUP_CHANNEL_NAMES.iter().find_map(|name| {
+        channels
+            .values()
+            .find(|c| c.kind == serenity::ChannelType::Text && c.name.eq_ignore_ascii_case(name))
+            .cloned()
+    })
 }
 
-// Schedule the task to send daily summaries
-async fn schedule_summary_task(ctx: serenity::Context, data: Data) {
-    println!("Starting summary scheduler");
-    
-    // Use a shorter interval for checking the time to avoid missing the target time
-    let check_interval = Duration::from_secs(60); // Check every minute
-    
-    loop {
-        // Get the current time and the scheduled summary time
-        let now = Local::now();
-        let (target_hour, target_minute) = *data.summary_time.lock().await;
-        
-        // Send summary if we're in the target time window
-        let should_send = now.hour() == target_hour && 
-                          now.minute() >= target_minute && 
-                          now.minute() < target_minute + 5; // 5-minute window
-        
-        if should_send {
-            println!("It's time for the summary! Current time: {}:{:02}", now.hour(), now.minute());
-            
-            // Send the summary with all current entries
-            if let Err(e) = send_summary(&ctx, &data).await {
-                eprintln!("Error sending summary: {}", e);
-            } else {
-                println!("Summary sent successfully");
+// Suggest a summary channel to each guild once, as long as no summary channel is configured
+async fn send_setup_hints(ctx: &serenity::Context, data: &Data, guild_ids: Vec<serenity::GuildId>) {
+    if data.summary_channel_id.lock().await.is_some() {
+        return;
+    }
+
+    let mut hints_sent = false;
+
+    for guild_id in guild_ids {
+        if data.setup_hint_sent.lock().await.contains(&guild_id) {
+            continue;
+        }
+
+        let channel = match find_standup_channel(ctx, guild_id).await {
+            Some(channel) => channel,
+            None => continue,
+        };
+
+        let hint = format!(
+            "I found this channel — use `/set_summary_channel #{}` to configure me.",
+            channel.name
+        );
+
+        match channel.id.say(ctx, hint).await {
+            Ok(message) => {
+                log_bot_message(data, &message, MessageType::SetupHint, 0).await;
+                println!("Sent setup hint to #{} in guild {}", channel.name, guild_id);
+                data.setup_hint_sent.lock().await.insert(guild_id);
+                hints_sent = true;
             }
-            
-            // Wait a bit more than the check window to avoid duplicate summaries within the same hour
-            time::sleep(Duration::from_secs(360)).await; // 6 minutes
-        } else {
-            // Wait for the next check interval
-            time::sleep(check_interval).await;
+            Err(e) => eprintln!("Failed to send setup hint to guild {}: {:?}", guild_id, e),
+        }
+    }
+
+    if hints_sent {
+        if let Err(e) = save_data(data).await {
+            eprintln!("Failed to save data after sending setup hints: {}", e);
         }
     }
 }
 
-// Send the summary and clear the stack
-async fn send_summary(ctx: &serenity::Context, data: &Data) -> Result<(), Error> {
-    let channel_id_option = *data.summary_channel_id.lock().await;
+// Permissions the bot needs in the summary channel to post summaries
+fn summary_channel_permissions() -> serenity::Permissions {
+    serenity::Permissions::VIEW_CHANNEL
+        | serenity::Permissions::SEND_MESSAGES
+        | serenity::Permissions::EMBED_LINKS
+        | serenity::Permissions::READ_MESSAGE_HISTORY
+}
 
-    let channel_id = match channel_id_option {
+// List the summary channel permissions the bot is missing in a channel
+async fn missing_channel_permissions(
+    ctx: &serenity::Context,
+    channel_id: serenity::ChannelId,
+) -> Result<Vec<&'static str>, Error> {
+    let channel = channel_id
+        .to_channel(ctx)
+        .await?
+        .guild()
+        .ok_or_else(|| BotError::UserError("The summary channel is not a server channel.".to_string()))?;
+
+    let permissions = channel.permissions_for_user(ctx, ctx.cache.current_user_id())?;
+
+    Ok(summary_channel_permissions().difference(permissions).get_permission_names())
+}
+
+// Where the bot posts messages other than summaries: the announcement channel if set, else the summary channel
+async fn announcement_channel(data: &Data) -> Option<serenity::ChannelId> {
+    match *data.announcement_channel_id.lock().await {
+        Some(id) => Some(id),
+        None => *data.summary_channel_id.lock().await,
+    }
+}
+
+// Kinds of operational notification, each with its own cooldown
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+enum NotificationType {
+    PermissionWarning,
+    SummaryFailure,
+    SchedulerRestarts,
+}
+
+const DEFAULT_NOTIFICATION_COOLDOWN_HOURS: u32 = 1;
+
+// Post a notification to the announcement channel unless one of the same type was posted within the cooldown
+async fn post_notification(ctx: &serenity::Context, data: &Data, kind: NotificationType, text: String) {
+    let now = Local::now();
+    let cooldown = chrono::Duration::hours(*data.notification_cooldown_hours.lock().await as i64);
+    if let Some(last) =
+        data.last_notification_timestamps.lock().await.get(&kind).copied().filter(|last| now - *last < cooldown)
+    {
+        println!("Skipping {:?} notification; the last one was sent at {}", kind, last.format("%H:%M"));
+        return;
+    }
+
+    let channel_id = match announcement_channel(data).await {
         Some(id) => id,
-        None => return Err("No summary channel set.".into()),
+        None => return,
     };
-    
-    // Create a snapshot of entries to avoid holding the lock during message sending
-    let entries_snapshot = {
-        let entries = data.standup_entries.lock().await;
-        if entries.is_empty() {
-            println!("No standup entries to summarize.");
-            return Ok(());
+    match channel_id.say(ctx, text).await {
+        Ok(message) => {
+            // Only start the cooldown once the notification actually went out
+            data.last_notification_timestamps.lock().await.insert(kind, now);
+            log_bot_message(data, &message, MessageType::Announcement, 0).await;
         }
-        entries.clone()
-    };
-    
-    // Group entries by user
-    let mut user_entries: HashMap<String, Vec<StandupEntry>> = HashMap::new();
-    for entry in entries_snapshot.iter() {
-        user_entries
-            .entry(entry.user_id.clone())
-            .or_insert_with(Vec::new)
-            .push(entry.clone());
+        Err(e) => eprintln!("Failed to post {:?} notification: {:?}", kind, e),
     }
+}
 
-    // Create the summary message
-    let mut message = "# Daily Standup Summary\n\n".to_string();
+// Check the summary channel's permissions and post a warning if any are missing
+async fn run_permission_check(ctx: &serenity::Context, data: &Data) {
+    let channel_id = match *data.summary_channel_id.lock().await {
+        Some(id) => id,
+        None => return,
+    };
 
-    for (_, user_entries) in user_entries.iter() {
-        // Use the most recent entry for each user
-        if let Some(latest) = user_entries.iter().max_by_key(|e| e.timestamp) {
-            message.push_str(&format!("## {}\n", latest.display_name));
-            message.push_str(&format!("**Did:** {}\n", latest.did));
-            message.push_str(&format!("**Plan:** {}\n", latest.plan));
-            message.push_str(&format!("**Blockers:** {}\n\n", latest.blockers));
+    match missing_channel_permissions(ctx, channel_id).await {
+        Ok(missing) if missing.is_empty() => println!("Permission check passed for summary channel"),
+        Ok(missing) => {
+            let warning = format!(
+                "⚠️ I'm missing permissions in <#{}> and may not be able to post the standup summary: {}",
+                channel_id,
+                missing.join(", ")
+            );
+            post_notification(ctx, data, NotificationType::PermissionWarning, warning).await;
         }
+        Err(e) => eprintln!("Failed to check summary channel permissions: {}", e),
     }
 
-    // Send the message with retry logic
-    let mut retries = 3;
-    let mut last_error = None;
-    
-    while retries > 0 {
-        match channel_id.say(ctx, &message).await {
-            Ok(_) => {
-                // Clear the entries only after successful sending
-                let mut entries = data.standup_entries.lock().await;
-                entries.clear();
-                drop(entries); // Release the lock
-                
-                // Save the updated data
-                if let Err(e) = save_data(data).await {
-                    eprintln!("Failed to save data after clearing entries: {}", e);
+    *data.last_perm_check.lock().await = Some(Local::now().date_naive());
+    if let Err(e) = save_data(data).await {
+        eprintln!("Failed to save data after permission check: {}", e);
+    }
+}
+
+// Number of days a /plan_for_tomorrow plan is kept before it expires
+const TOMORROW_PLAN_EXPIRY_DAYS: i64 = 7;
+
+// Number of days skip entries are kept
+const SKIP_LOG_RETENTION_DAYS: i64 = 30;
+
+// Remove expired plans and old skips, saving only if anything changed
+async fn cleanup_expired_data(data: &Data, now: DateTime<Local>) {
+    let expiry_hours = *data.pending_entry_expiry_hours.lock().await;
+    let period_start = data.summary_schedule.lock().await.period_start(now.date_naive());
+    let expired_entries: Vec<StandupEntry> = {
+        let mut entries = data.standup_entries.lock().await;
+        let (expired, kept) = entries
+            .drain(..)
+            .partition(|e| is_entry_expired(e, now, expiry_hours, period_start));
+        *entries = kept;
+        expired
+    };
+
+    for entry in &expired_entries {
+        println!(
+            "Removed expired standup entry from {} submitted at {}",
+            entry.display_name,
+            entry.timestamp.format("%Y-%m-%d %H:%M")
+        );
+    }
+
+    let expiry_date = now.date_naive() - chrono::Duration::days(TOMORROW_PLAN_EXPIRY_DAYS);
+
+    let expired_plans = {
+        let before = data.tomorrow_plans.len();
+        data.tomorrow_plans.retain(|_, p| p.saved_on > expiry_date);
+        before - data.tomorrow_plans.len()
+    };
+
+    if expired_plans > 0 {
+        println!("Removed {} expired tomorrow plans", expired_plans);
+    }
+
+    let skip_expiry_date = now.date_naive() - chrono::Duration::days(SKIP_LOG_RETENTION_DAYS);
+    let expired_skips = {
+        let mut skip_log = data.skip_log.lock().await;
+        let before = skip_log.len();
+        skip_log.retain(|s| s.date > skip_expiry_date);
+        before - skip_log.len()
+    };
+
+    if expired_skips > 0 {
+        println!("Removed {} old skip entries", expired_skips);
+    }
+
+    if !expired_entries.is_empty() || expired_plans > 0 || expired_skips > 0 {
+        if let Err(e) = save_data(data).await {
+            eprintln!("Failed to save data after removing expired data: {}", e);
+        }
+    }
+}
+
+// Hours a pending entry is kept before it's considered too old for a summary
+const DEFAULT_ENTRY_EXPIRY_HOURS: u32 = 48;
+
+// Whether a pending entry is older than the configured expiry (0 disables expiry).
+// Entries from the current summary period are always kept so weekly summaries don't lose early submissions.
+fn is_entry_expired(entry: &StandupEntry, now: DateTime<Local>, expiry_hours: u32, period_start: NaiveDate) -> bool {
+    expiry_hours > 0
+        && entry.timestamp.date_naive() < period_start
+        && now - entry.timestamp > chrono::Duration::hours(expiry_hours as i64)
+}
+
+// Schedule the task to send daily summaries.
+// Every shard runs its own scheduler, but a shard only posts to guilds assigned to it
+// ((guild_id >> 22) % total_shards == shard_id); send_summary checks this before posting.
+async fn schedule_summary_task(ctx: serenity::Context, data: Data) {
+    Scheduler::new(ctx, data).run().await;
+}
+
+// Restarts within an hour after which the server is told the scheduler keeps crashing
+const SCHEDULER_RESTART_ALERT_THRESHOLD: usize = 3;
+
+// Run the scheduler in its own task and restart it if it panics, so summaries don't silently stop
+async fn supervise_scheduler(ctx: serenity::Context, data: Data) {
+    let mut recent_restarts: VecDeque<DateTime<Local>> = VecDeque::new();
+
+    loop {
+        let handle = tokio::spawn(schedule_summary_task(ctx.clone(), data.clone()));
+        match handle.await {
+            Err(e) => eprintln!("Scheduler panicked: {:?}. Restarting in 30s.", e),
+            Ok(()) => eprintln!("Scheduler stopped unexpectedly. Restarting in 30s."),
+        }
+        time::sleep(Duration::from_secs(30)).await;
+
+        let restarts = data.scheduler_restarts.fetch_add(1, Ordering::Relaxed) + 1;
+        let now = Local::now();
+        recent_restarts.push_back(now);
+        while recent_restarts.front().is_some_and(|t| now - *t > chrono::Duration::hours(1)) {
+            recent_restarts.pop_front();
+        }
+
+        if recent_restarts.len() > SCHEDULER_RESTART_ALERT_THRESHOLD {
+            let alert = format!(
+                "⚠️ My summary scheduler has crashed {} times in the last hour ({} restarts in total). \
+                 Summaries may be missed; please check the bot's logs.",
+                recent_restarts.len(),
+                restarts
+            );
+            post_notification(&ctx, &data, NotificationType::SchedulerRestarts, alert).await;
+        }
+    }
+}
+
+// Minutes after the summary time during which the scheduler will still send the summary
+const SUMMARY_WINDOW_MINUTES: u32 = 5;
+
+// Whether `now` is a summary day and inside the window after the summary time
+fn should_fire_at(schedule: SummarySchedule, (hour, minute): (u32, u32), now: DateTime<Local>) -> bool {
+    // Compare minutes since midnight so a window that crosses the hour (e.g. 17:58) isn't cut short
+    let target = hour * 60 + minute;
+    let current = now.hour() * 60 + now.minute();
+    
+    schedule.fires_on(now.date_naive()) && current >= target && current < target + SUMMARY_WINDOW_MINUTES
+}
+
+// Drives the bot's periodic work: sending summaries and the housekeeping around them
+struct Scheduler {
+    ctx: serenity::Context,
+    data: Data,
+}
+
+impl Scheduler {
+    fn new(ctx: serenity::Context, data: Data) -> Self {
+        Scheduler { ctx, data }
+    }
+
+    async fn run(&self) {
+        println!("Starting summary scheduler");
+        self.log_next_fire_time().await;
+        
+        // Use a shorter interval for checking the time to avoid missing the target time
+        let check_interval = Duration::from_secs(60); // Check every minute
+        
+        loop {
+            let now = Local::now();
+            self.run_maintenance(now).await;
+            
+            if self.should_fire_now(now).await {
+                println!("It's time for the summary! Current time: {}:{:02}", now.hour(), now.minute());
+                
+                // Send the summary with all current entries
+                if let Err(e) = self.on_fire().await {
+                    eprintln!("Error sending summary: {}", e);
+                    // Only Discord failures are worth telling the server about; other errors are
+                    // configuration or sharding issues that are already logged
+                    if let BotError::DiscordApi(_) = e {
+                        post_notification(
+                            &self.ctx,
+                            &self.data,
+                            NotificationType::SummaryFailure,
+                            format!("⚠️ I couldn't post the standup summary: {}", e),
+                        )
+                        .await;
+                    }
+                } else {
+                    println!("Summary sent successfully");
+                }
+                self.log_next_fire_time().await;
+                
+                // Wait a bit more than the check window to avoid duplicate summaries within the same hour
+                time::sleep(Duration::from_secs(360)).await; // 6 minutes
+            } else {
+                // Wait for the next check interval
+                time::sleep(check_interval).await;
+            }
+        }
+    }
+
+    // Work done on every tick regardless of whether a summary is due
+    async fn run_maintenance(&self, now: DateTime<Local>) {
+        // Drop saved data that has gone stale
+        cleanup_expired_data(&self.data, now).await;
+        
+        // Close the summary time poll once it has run its course
+        finish_vote_if_due(&self.ctx, &self.data, now).await;
+        
+        // Check the summary channel's permissions once every Monday
+        let perm_check_due = now.weekday() == Weekday::Mon
+            && *self.data.perm_check_enabled.lock().await
+            && *self.data.last_perm_check.lock().await != Some(now.date_naive());
+        if perm_check_due {
+            run_permission_check(&self.ctx, &self.data).await;
+        }
+    }
+
+    async fn should_fire_now(&self, now: DateTime<Local>) -> bool {
+        let summary_time = *self.data.summary_time.lock().await;
+        let schedule = *self.data.summary_schedule.lock().await;
+        should_fire_at(schedule, summary_time, now)
+    }
+
+    async fn next_fire_time(&self, now: DateTime<Local>) -> Option<DateTime<Local>> {
+        let summary_time = *self.data.summary_time.lock().await;
+        self.data.summary_schedule.lock().await.next_fire(now, summary_time)
+    }
+
+    async fn log_next_fire_time(&self) {
+        if let Some(next) = self.next_fire_time(Local::now()).await {
+            println!("Next summary scheduled for {}", next.format("%Y-%m-%d %H:%M"));
+        }
+    }
+
+    async fn on_fire(&self) -> Result<(), Error> {
+        send_summary(&self.ctx, &self.data).await
+    }
+}
+
+// Blocker answers that mean "no blockers" until admins change the list
+const DEFAULT_TRIVIAL_BLOCKER_PHRASES: [&str; 9] =
+    ["-", "none", "no", "n/a", "na", "nothing", "no blockers", "nope", "all good"];
+
+fn default_trivial_blocker_phrases() -> HashSet<String> {
+    DEFAULT_TRIVIAL_BLOCKER_PHRASES.iter().map(|p| p.to_string()).collect()
+}
+
+// Normalize a phrase so comparisons ignore case and surrounding whitespace
+fn normalize_phrase(text: &str) -> String {
+    text.trim().to_lowercase()
+}
+
+// Check whether a blockers answer is empty or just a way of saying there are no blockers
+fn is_trivial_blocker(text: &str, phrases: &HashSet<String>) -> bool {
+    let normalized = normalize_phrase(text);
+    normalized.is_empty() || phrases.contains(&normalized)
+}
+
+fn default_blocker_keywords() -> HashMap<BlockerSeverity, Vec<String>> {
+    let keywords = |words: &[&str]| words.iter().map(|w| w.to_string()).collect::<Vec<_>>();
+    HashMap::from([
+        (BlockerSeverity::Critical, keywords(&["production", "outage", "down", "incident"])),
+        (BlockerSeverity::High, keywords(&["blocked", "urgent", "stuck", "broken"])),
+        (BlockerSeverity::Medium, keywords(&["waiting", "slow", "delayed"])),
+    ])
+}
+
+// The highest severity whose keywords appear in a blockers answer. Single-word keywords must match
+// a whole word; keywords with spaces match anywhere in the text.
+fn detect_severity(text: &str, keywords: &HashMap<BlockerSeverity, Vec<String>>) -> Option<BlockerSeverity> {
+    let text = normalize_phrase(text);
+    let words: HashSet<&str> = text.split(|c: char| !c.is_alphanumeric()).filter(|w| !w.is_empty()).collect();
+
+    keywords
+        .iter()
+        .filter(|(_, list)| {
+            list.iter().any(|keyword| {
+                if keyword.contains(char::is_whitespace) {
+                    text.contains(keyword.as_str())
+                } else {
+                    words.contains(keyword.as_str())
+                }
+            })
+        })
+        .map(|(severity, _)| *severity)
+        .max()
+}
+
+// Order summary entries
+fn sort_entries(entries: &mut [StandupEntry], order: SortOrder) {
+    match order {
+        SortOrder::Alphabetical => entries.sort_unstable_by(|a, b| {
+            a.display_name
+                .to_lowercase()
+                .cmp(&b.display_name.to_lowercase())
+                .then_with(|| a.cmp(b))
+        }),
+        SortOrder::SubmissionTime => entries.sort_unstable(),
+        SortOrder::Random => entries.shuffle(&mut rand::thread_rng()),
+    }
+}
+
+// Substitute `{name}` variables in a template, leaving unknown variables as literal text
+fn render_template(template: &str, variables: &HashMap<&str, String>) -> String {
+    let mut output = String::with_capacity(template.len());
+    let mut rest = template;
+
+    while let Some(start) = rest.find('{') {
+        output.push_str(&rest[..start]);
+        let after_brace = &rest[start + 1..];
+
+        match after_brace.find('}') {
+            // Another `{` before the closing brace, so this one is plain text, e.g. `{a{b}`
+            Some(end) if after_brace[..end].contains('{') => {
+                output.push('{');
+                rest = after_brace;
+            }
+            Some(end) => {
+                let name = &after_brace[..end];
+                match variables.get(name) {
+                    Some(value) => output.push_str(value),
+                    None => {
+                        eprintln!("Unknown template variable {{{}}} left as-is", name);
+                        output.push_str(&rest[start..start + end + 2]);
+                    }
+                }
+                rest = &after_brace[end + 1..];
+            }
+            None => {
+                // No closing brace, so the rest is plain text
+                output.push_str(&rest[start..]);
+                rest = "";
+            }
+        }
+    }
+
+    output.push_str(rest);
+    output
+}
+
+// Variables available to the custom entry format
+fn entry_variables(entry: &StandupEntry, avatar_url: String) -> HashMap<&'static str, String> {
+    HashMap::from([
+        ("avatar_url", avatar_url),
+        ("mention", format!("<@{}>", entry.user_id)),
+        ("display_name", entry.display_name.clone()),
+        ("did", entry.did.clone()),
+        ("plan", entry.plan.clone()),
+        ("blockers", entry.blockers.clone()),
+        ("timestamp", entry.timestamp.format("%H:%M").to_string()),
+        ("attachments", format_attachments(&entry.attachments)),
+    ])
+}
+
+// Most links a user can attach to one standup
+const MAX_ATTACHMENTS: usize = 5;
+
+fn attachment_url_regex() -> &'static Regex {
+    static REGEX: OnceLock<Regex> = OnceLock::new();
+    REGEX.get_or_init(|| Regex::new(r"^https://[^\s/?#]+\.[^\s/?#]+([/?#]\S*)?$").unwrap())
+}
+
+// Split the links given to /standup on spaces or commas and check they are all https:// URLs
+fn parse_attachments(input: &str) -> Result<Vec<String>, String> {
+    let links: Vec<String> = input
+        .split(|c: char| c.is_whitespace() || c == ',')
+        .filter(|link| !link.is_empty())
+        .map(str::to_string)
+        .collect();
+
+    if links.len() > MAX_ATTACHMENTS {
+        return Err(format!("You can attach at most {} links.", MAX_ATTACHMENTS));
+    }
+    if let Some(invalid) = links.iter().find(|link| !attachment_url_regex().is_match(link)) {
+        return Err(format!("'{}' isn't a valid link. Links must start with https://.", invalid));
+    }
+
+    Ok(links)
+}
+
+// The host part of a validated attachment URL, used as the link text
+fn attachment_domain(url: &str) -> &str {
+    let rest = url.trim_start_matches("https://");
+    rest.split(['/', '?', '#']).next().unwrap_or(rest)
+}
+
+// Render attachments as Markdown links, wrapped in <> so Discord doesn't embed a preview for each
+fn format_attachments(attachments: &[String]) -> String {
+    attachments
+        .iter()
+        .map(|url| format!("[{}](<{}>)", attachment_domain(url), url))
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+// Look up a user's avatar URL, falling back to an empty string if the user can't be fetched
+async fn fetch_avatar_url(ctx: &serenity::Context, user_id: &str) -> String {
+    let user_id = match user_id.parse::<u64>() {
+        Ok(id) => serenity::UserId(id),
+        Err(_) => return String::new(),
+    };
+
+    match user_id.to_user(ctx).await {
+        Ok(user) => user.face(),
+        Err(e) => {
+            eprintln!("Failed to fetch user {} for avatar: {:?}", user_id, e);
+            String::new()
+        }
+    }
+}
+
+// How long to wait after Discord rate limits a request that serenity didn't retry itself
+const RATE_LIMIT_WAIT: Duration = Duration::from_secs(30);
+
+// Check whether an error is Discord rejecting a request with HTTP 429 Too Many Requests.
+// Serenity's own ratelimiter already waits out the Retry-After header for most requests and
+// doesn't keep it in the error, so a 429 that reaches us gets a conservative fixed wait instead.
+fn is_rate_limited(error: &serenity::Error) -> bool {
+    match error {
+        serenity::Error::Http(http_error) => {
+            http_error.status_code() == Some(serenity::StatusCode::TOO_MANY_REQUESTS)
+        }
+        _ => false,
+    }
+}
+
+// Shortest time between channel topic edits, so a burst of changes edits the topic at most once a minute
+const TOPIC_UPDATE_DEBOUNCE_SECS: i64 = 60;
+
+// Show the next and last summary in the summary channel's topic, if enabled
+async fn update_channel_topic(
+    ctx: &serenity::Context,
+    data: &Data,
+    channel_id: serenity::ChannelId,
+    submitted: usize,
+) {
+    if !*data.auto_update_topic.lock().await {
+        return;
+    }
+
+    let now = Local::now();
+    {
+        let mut last_update = data.last_topic_update.lock().await;
+        if last_update.is_some_and(|last| now - last < chrono::Duration::seconds(TOPIC_UPDATE_DEBOUNCE_SECS)) {
+            println!("Skipping channel topic update; it was updated less than a minute ago");
+            return;
+        }
+        *last_update = Some(now);
+    }
+
+    let summary_time = *data.summary_time.lock().await;
+    let next = match data.summary_schedule.lock().await.next_fire(now, summary_time) {
+        Some(next) => next.format("%a %H:%M").to_string(),
+        None => "not scheduled".to_string(),
+    };
+    let topic = format!(
+        "Next standup: {} | Last summary: {} | {} submitted",
+        next,
+        now.format("%a %b %-d"),
+        submitted
+    );
+
+    if let Err(e) = channel_id.edit(ctx, |c| c.topic(topic)).await {
+        if let serenity::Error::Http(http_error) = &e {
+            if http_error.status_code() == Some(serenity::StatusCode::FORBIDDEN) {
+                eprintln!("Can't update the summary channel topic: the bot needs the 'Manage Channels' permission");
+                return;
+            }
+        }
+        eprintln!("Failed to update summary channel topic: {:?}", e);
+    }
+}
+
+// Send the summary and clear the stack
+async fn send_summary(ctx: &serenity::Context, data: &Data) -> Result<(), Error> {
+    let channel_id_option = *data.summary_channel_id.lock().await;
+
+    let channel_id = match channel_id_option {
+        Some(id) => id,
+        None => return Err(BotError::UserError("No summary channel set.".to_string())),
+    };
+    
+    // Leave the summary to the shard that handles the channel's guild
+    if let Some(channel) = ctx.cache.guild_channel(channel_id) {
+        if !data.handles_guild(channel.guild_id) {
+            return Err(BotError::Internal(format!(
+                "Summary channel's guild {} is handled by another shard.",
+                channel.guild_id
+            )));
+        }
+    }
+    
+    // Create a snapshot of entries to avoid holding the lock during message sending
+    let mut entries_snapshot = data.standup_entries.lock().await.clone();
+    
+    // Leave out entries that are too old, e.g. because the bot was down when they should have been summarized
+    let expiry_hours = *data.pending_entry_expiry_hours.lock().await;
+    let now = Local::now();
+    let period_start = data.summary_schedule.lock().await.period_start(now.date_naive());
+    let submitted = entries_snapshot.len();
+    entries_snapshot.retain(|e| !is_entry_expired(e, now, expiry_hours, period_start));
+    if entries_snapshot.len() < submitted {
+        println!("Left {} expired entries out of the summary", submitted - entries_snapshot.len());
+    }
+    
+    if entries_snapshot.is_empty() {
+        println!("No standup entries to summarize.");
+        return Ok(());
+    }
+    
+    // Group entries by user
+    let mut user_entries: HashMap<String, Vec<StandupEntry>> = HashMap::new();
+    for entry in entries_snapshot.iter() {
+        user_entries
+            .entry(entry.user_id.clone())
+            .or_default()
+            .push(entry.clone());
+    }
+
+    // Use the most recent entry for each user, in the configured order
+    let mut latest_entries: Vec<StandupEntry> = user_entries
+        .into_values()
+        .filter_map(|entries| entries.into_iter().max())
+        .collect();
+    let sort_order = *data.summary_sort_order.lock().await;
+    sort_entries(&mut latest_entries, sort_order);
+
+    let entry_format = data.entry_format.lock().await.clone();
+    let schedule = *data.summary_schedule.lock().await;
+
+    // Create the summary message
+    let mut message = format!("# {} Standup Summary\n\n", schedule.period_name());
+
+    for latest in latest_entries.iter() {
+        match &entry_format {
+            Some(template) => {
+                // Only look up the user's avatar when the template actually uses it
+                let avatar_url = if template.contains("{avatar_url}") {
+                    fetch_avatar_url(ctx, &latest.user_id).await
+                } else {
+                    String::new()
+                };
+                message.push_str(&render_template(template, &entry_variables(latest, avatar_url)));
+                message.push_str("\n\n");
+            }
+            None => {
+                message.push_str(&format!("## {}\n", latest.display_name));
+                message.push_str(&format!("**Did:** {}\n", latest.did));
+                message.push_str(&format!("**Plan:** {}\n", latest.plan));
+                message.push_str(&format!("**Blockers:** {}\n", latest.blockers));
+                if !latest.attachments.is_empty() {
+                    message.push_str(&format!("**Links:** {}\n", format_attachments(&latest.attachments)));
+                }
+                message.push('\n');
+            }
+        }
+    }
+
+    // List approved skips from this summary period
+    let period_start = schedule.period_start(Local::now().date_naive());
+    for skip in data.skip_log.lock().await.iter().filter(|s| s.approved && s.date >= period_start) {
+        match &skip.reason {
+            Some(reason) => message.push_str(&format!("⏭ {} skipped (reason: {})\n", skip.display_name, reason)),
+            None => message.push_str(&format!("⏭ {} skipped\n", skip.display_name)),
+        }
+    }
+
+    // Finish with the custom footer, if one is set
+    if let Some(footer) = data.summary_footer.lock().await.as_ref() {
+        let variables = HashMap::from([
+            ("date", Local::now().format("%Y-%m-%d").to_string()),
+            ("count", latest_entries.len().to_string()),
+        ]);
+        message.push_str(&format!("\n-# {}", render_template(footer, &variables)));
+    }
+
+    // Discord ignores image URLs it can't load, so a broken image just doesn't show
+    let image_url = data.summary_image_url.lock().await.as_ref().map(|url| {
+        render_template(url, &HashMap::from([("date", Local::now().format("%Y-%m-%d").to_string())]))
+    });
+
+    // Send the message with retry logic
+    let mut retries = 3;
+    let mut last_error = None;
+    let mut backoff = Duration::from_secs(5);
+    
+    while retries > 0 {
+        let result = channel_id
+            .send_message(ctx, |m| {
+                m.content(&message);
+                if let Some(url) = &image_url {
+                    m.embed(|e| e.image(url));
                 }
+                m
+            })
+            .await;
+        match result {
+            Ok(sent) => {
+                log_bot_message(data, &sent, MessageType::Summary, latest_entries.len()).await;
+                
+                // Things are working again, so the next problem should be reported straight away
+                data.last_notification_timestamps.lock().await.clear();
+                
+                // Clear the entries only after successful sending
+                let mut entries = data.standup_entries.lock().await;
+                entries.clear();
+                drop(entries); // Release the lock
+                
+                // Save the updated data
+                if let Err(e) = save_data(data).await {
+                    eprintln!("Failed to save data after clearing entries: {}", e);
+                }
+                
+                update_channel_topic(ctx, data, channel_id, latest_entries.len()).await;
+                
+                // Show that the summary went out, then go back to the running tally
+                update_digest(ctx, data, format!("✅ Summary sent · {} submitted", latest_entries.len())).await;
+                let (ctx, data) = (ctx.clone(), data.clone());
+                tokio::spawn(async move {
+                    time::sleep(DIGEST_RESET_DELAY).await;
+                    update_digest(&ctx, &data, digest_tally(&data).await).await;
+                });
                 
                 return Ok(());
             }
             Err(e) => {
                 eprintln!("Error sending summary (retries left: {}): {:?}", retries - 1, e);
-                last_error = Some(format!("Discord API error: {:?}", e));
                 retries -= 1;
-                time::sleep(Duration::from_secs(5)).await; // Wait before retrying
+                
+                // Wait before retrying: back off for rate limits, exponentially for anything else
+                let wait = if is_rate_limited(&e) {
+                    data.rate_limit_hits.fetch_add(1, Ordering::Relaxed);
+                    RATE_LIMIT_WAIT
+                } else {
+                    backoff
+                };
+                last_error = Some(e);
+                time::sleep(wait).await;
+                backoff *= 2;
             }
         }
     }
     
-    Err(last_error.unwrap_or_else(|| "Failed to send summary after multiple attempts".into()).into())
+    Err(match last_error {
+        Some(e) => BotError::DiscordApi(e),
+        None => BotError::Internal("Failed to send summary after multiple attempts".to_string()),
+    })
 }
-#[poise::command(slash_command, ephemeral)]
-/// Submit your daily standup update
-async fn standup(
-    ctx: Context<'_>,
-    #[description = "What you did"] did: String,
-    #[description = "What you plan to do"] plan: String,
-    #[description = "Any blockers or problems"] blockers: String,
-) -> Result<(), Error> {
-    let user = ctx.author();
-    
-    // Get the user's display name (nickname if available, otherwise username)
-    let display_name = if let Some(member) = ctx.author_member().await {
-        member.nick.clone().unwrap_or_else(|| user.name.clone())
-    } else {
-        user.name.clone()
+// Check that the command author has the 'Manage Channels' permission
+async fn require_manage_channels(ctx: Context<'_>) -> Result<(), Error> {
+    match ctx.author_member().await {
+        Some(member) if member.permissions(ctx).is_ok_and(|p| p.manage_channels()) => Ok(()),
+        Some(_) => Err(BotError::PermissionDenied(
+            "You need 'Manage Channels' permission to use this command.".to_string(),
+        )),
+        None => Err(BotError::UserError("This command can only be used in a server.".to_string())),
+    }
+}
+
+// How long the "summary sent" digest stays up before it goes back to the running tally
+const DIGEST_RESET_DELAY: Duration = Duration::from_secs(600);
+
+// The running tally shown in the digest channel
+async fn digest_tally(data: &Data) -> String {
+    let (count, last) = {
+        let entries = data.standup_entries.lock().await;
+        (entries.len(), entries.iter().max().map(|e| e.display_name.clone()))
     };
-    
-    // Create a new standup entry
-    let entry = StandupEntry {
-        user_id: user.id.to_string(),
-        display_name,
-        did,
-        plan,
-        blockers,
-        timestamp: Local::now(),
+
+    let summary_time = *data.summary_time.lock().await;
+    let now = Local::now();
+    let next = match data.summary_schedule.lock().await.next_fire(now, summary_time) {
+        Some(next) => {
+            let minutes = (next - now).num_minutes();
+            format!("Next summary in {}h {:02}m", minutes / 60, minutes % 60)
+        }
+        None => "No summary scheduled".to_string(),
     };
-    
-    // Add the entry to our stack
-    {
-        let mut entries = ctx.data().standup_entries.lock().await;
-        
-        // Remove any previous entries from the same user (keep only latest)
-        entries.retain(|e| e.user_id != user.id.to_string());
-        
-        // Add the new entry
-        entries.push(entry);
-        
-        // Release the lock before saving
-        drop(entries);
+
+    match last {
+        Some(last) => format!("📊 {} submitted · Last: {} · {}", count, last, next),
+        None => format!("📊 No standups submitted yet · {}", next),
+    }
+}
+
+// Show `content` in the digest channel by editing the digest message, or posting a new one
+// if there isn't one yet or it was deleted
+async fn update_digest(ctx: &serenity::Context, data: &Data, content: String) {
+    let channel_id = match *data.digest_channel_id.lock().await {
+        Some(id) => id,
+        None => return,
+    };
+
+    let mut message_id = data.digest_message_id.lock().await;
+    if let Some(id) = *message_id {
+        match channel_id.edit_message(ctx, id, |m| m.content(&content)).await {
+            Ok(_) => return,
+            Err(serenity::Error::Http(e)) if e.status_code() == Some(serenity::StatusCode::NOT_FOUND) => {
+                println!("Digest message was deleted; posting a new one");
+            }
+            Err(e) => {
+                eprintln!("Failed to update digest message: {:?}", e);
+                return;
+            }
+        }
+    }
+
+    match channel_id.say(ctx, &content).await {
+        Ok(message) => {
+            *message_id = Some(message.id);
+            drop(message_id);
+            log_bot_message(data, &message, MessageType::Digest, 0).await;
+            if let Err(e) = save_data(data).await {
+                eprintln!("Failed to save data after posting digest message: {}", e);
+            }
+        }
+        Err(e) => eprintln!("Failed to post digest message: {:?}", e),
+    }
+}
+
+// Most messages kept in the message log; the oldest are dropped first
+const MESSAGE_LOG_LIMIT: usize = 10_000;
+
+// Record a message the bot posted. The log is saved along with the rest of the data.
+async fn log_bot_message(data: &Data, message: &serenity::Message, message_type: MessageType, entry_count: usize) {
+    let mut log = data.message_log.lock().await;
+    log.push_back(MessageLogEntry {
+        message_id: message.id,
+        channel_id: message.channel_id,
+        message_type,
+        timestamp: Local::now(),
+        entry_count,
+    });
+    while log.len() > MESSAGE_LOG_LIMIT {
+        log.pop_front();
+    }
+}
+
+// Post a one-line notification about a new submission to the live feed channel, if one is set
+async fn post_live_feed_notification(ctx: &serenity::Context, data: &Data, entry: &StandupEntry) {
+    let channel_id = match *data.live_feed_channel_id.lock().await {
+        Some(id) => id,
+        None => return,
+    };
+
+    let phrases = data.trivial_blocker_phrases.lock().await.clone();
+    let (icon, blocker_status) = if is_trivial_blocker(&entry.blockers, &phrases) {
+        ("✅", "no blockers".to_string())
+    } else {
+        match entry.blocker_severity {
+            Some(severity) => (severity.icon(), format!("{} blocker reported", severity.name().to_lowercase())),
+            None => ("⚠️", "blocker reported".to_string()),
+        }
+    };
+    let notification = format!(
+        "{} {} submitted at {} — {}",
+        icon,
+        entry.display_name,
+        entry.timestamp.format("%H:%M"),
+        blocker_status
+    );
+
+    match channel_id.say(ctx, notification).await {
+        Ok(message) => log_bot_message(data, &message, MessageType::LiveFeed, 1).await,
+        Err(e) => eprintln!("Failed to post to live feed channel: {:?}", e),
+    }
+}
+
+// Get the author's display name (nickname if available, otherwise username)
+async fn author_display_name(ctx: Context<'_>) -> String {
+    let user = ctx.author();
+    if let Some(member) = ctx.author_member().await {
+        member.nick.clone().unwrap_or_else(|| user.name.clone())
+    } else {
+        user.name.clone()
+    }
+}
+
+#[poise::command(slash_command, category = "Standup", ephemeral)]
+/// Submit your daily standup update
+async fn standup(
+    ctx: Context<'_>,
+    #[description = "What you did"] did: String,
+    #[description = "Any blockers or problems"] blockers: String,
+    #[description = "What you plan to do (defaults to your /plan_for_tomorrow plan)"] plan: Option<String>,
+    #[description = "Up to 5 related https:// links (PRs, docs, issues), separated by spaces"] links: Option<String>,
+) -> Result<(), Error> {
+    let user = ctx.author();
+    
+    let attachments = match links {
+        Some(links) => parse_attachments(&links).map_err(BotError::UserError)?,
+        None => Vec::new(),
+    };
+    
+    // Use the plan saved with /plan_for_tomorrow if none was given; either way the saved plan is used up
+    let saved_plan = ctx.data().tomorrow_plans.remove(&user.id.to_string());
+    let plan = match plan.or(saved_plan.map(|(_, p)| p.plan)) {
+        Some(plan) => plan,
+        None => {
+            return Err(BotError::UserError(
+                "Please tell us what you plan to do, or save a plan in advance with /plan_for_tomorrow.".to_string(),
+            ));
+        }
+    };
+    
+    let display_name = author_display_name(ctx).await;
+    
+    let blocker_severity = if is_trivial_blocker(&blockers, &*ctx.data().trivial_blocker_phrases.lock().await) {
+        None
+    } else {
+        detect_severity(&blockers, &*ctx.data().blocker_keywords.lock().await)
+    };
+    
+    // Create a new standup entry
+    let entry = StandupEntry {
+        user_id: user.id.to_string(),
+        display_name,
+        did,
+        plan,
+        blockers,
+        timestamp: Local::now(),
+        attachments,
+        blocker_severity,
+    };
+    
+    // Add the entry to our stack
+    {
+        let mut entries = ctx.data().standup_entries.lock().await;
+        
+        // Remove any previous entries from the same user (keep only latest)
+        entries.retain(|e| e.user_id != user.id.to_string());
+        
+        // Add the new entry
+        entries.push(entry.clone());
+        
+        // Release the lock before saving
+        drop(entries);
+    }
+    
+    // Mention which summary the entry will appear in when summaries aren't daily
+    let recorded_for = match *ctx.data().summary_schedule.lock().await {
+        SummarySchedule::Daily => String::new(),
+        SummarySchedule::Weekly { day } | SummarySchedule::BiWeekly { day, .. } => {
+            format!(" for the {} summary", weekday_name(day))
+        }
+    };
+    
+    // Save the updated data
+    if let Err(e) = save_data(ctx.data()).await {
+        eprintln!("Failed to save data after standup submission: {}", e);
+        ctx.say(format!("Your standup has been recorded{}, but there was an error saving the data.", recorded_for)).await?;
+    } else {
+        ctx.say(format!("Your standup has been recorded{}. Thanks!", recorded_for)).await?;
+    }
+    
+    // Send a copy of the submission by DM for users who asked for one
+    let wants_dm = ctx
+        .data()
+        .user_prefs
+        .get(&entry.user_id)
+        .is_some_and(|prefs| prefs.dm_confirmation);
+    if wants_dm {
+        send_dm_confirmation(ctx, &entry).await;
+    }
+    
+    // Let leads know about the submission; failures here shouldn't affect the user
+    post_live_feed_notification(ctx.serenity_context(), ctx.data(), &entry).await;
+    update_digest(ctx.serenity_context(), ctx.data(), digest_tally(ctx.data()).await).await;
+    
+    Ok(())
+}
+
+// Longest did/plan/blockers answer copied into a confirmation DM, so the DM stays under
+// Discord's message length limit
+const DM_CONFIRMATION_FIELD_CHARS: usize = 600;
+
+// Discord's error code for "Cannot send messages to this user"
+const CANNOT_MESSAGE_USER_CODE: isize = 50007;
+
+// Check whether an error is Discord refusing to DM a user, usually because their DMs are closed
+fn is_dm_closed(error: &serenity::Error) -> bool {
+    match error {
+        serenity::Error::Http(http_error) => match http_error.as_ref() {
+            serenity::HttpError::UnsuccessfulRequest(response) => {
+                response.status_code == serenity::StatusCode::FORBIDDEN
+                    || response.error.code == CANNOT_MESSAGE_USER_CODE
+            }
+            _ => false,
+        },
+        _ => false,
+    }
+}
+
+// DM the user a copy of their submission. If their DMs are closed the preference is
+// turned off so we don't keep trying on every submission.
+async fn send_dm_confirmation(ctx: Context<'_>, entry: &StandupEntry) {
+    let content = format!(
+        "📝 Your standup for {}:\n**Did:** {}\n**Plan:** {}\n**Blockers:** {}",
+        entry.timestamp.format("%b %-d"),
+        preview(&entry.did, DM_CONFIRMATION_FIELD_CHARS),
+        preview(&entry.plan, DM_CONFIRMATION_FIELD_CHARS),
+        preview(&entry.blockers, DM_CONFIRMATION_FIELD_CHARS)
+    );
+
+    let user = ctx.author();
+    match user.direct_message(ctx, |m| m.content(content)).await {
+        Ok(message) => log_bot_message(ctx.data(), &message, MessageType::DmConfirmation, 1).await,
+        Err(e) if !is_dm_closed(&e) => {
+            eprintln!("Failed to send standup confirmation DM to {}: {:?}", user.id, e);
+        }
+        Err(e) => {
+            eprintln!("Can't DM {} a standup confirmation, turning DM confirmations off: {}", user.id, e);
+            if let Some(mut prefs) = ctx.data().user_prefs.get_mut(&entry.user_id) {
+                prefs.dm_confirmation = false;
+            }
+            if let Err(e) = save_data(ctx.data()).await {
+                eprintln!("Failed to save data after disabling DM confirmation: {}", e);
+            }
+            if let Err(e) = ctx.say("I couldn't send you a confirmation DM, so DM confirmations have been turned off. Allow DMs from server members and run /my_config set to turn them back on.").await {
+                eprintln!("Failed to tell {} that DM confirmations were turned off: {:?}", user.id, e);
+            }
+        }
+    }
+}
+
+#[poise::command(slash_command, category = "Configuration", ephemeral)]
+/// Set the channel for daily summaries
+async fn set_summary_channel(
+    ctx: Context<'_>,
+    #[description = "The channel for daily summaries"] channel_id: serenity::ChannelId,
+) -> Result<(), Error> {
+    // Check if the command is being used in a server
+    let guild_id = ctx
+        .guild_id()
+        .ok_or_else(|| BotError::UserError("This command can only be used in a server.".to_string()))?;
+
+    // Fetch the guild to get the owner ID
+    let guild = ctx.http().get_guild(guild_id.0).await?;
+
+    // Check if the user is the server owner or has the "Manage Channels" permission
+    let is_owner = ctx.author().id == guild.owner_id;
+    let has_permission = ctx.author_member().await
+        .is_some_and(|member| member.permissions(ctx).is_ok_and(|p| p.manage_channels()));
+
+    if !is_owner && !has_permission {
+        return Err(BotError::PermissionDenied(
+            "You need 'Manage Channels' permission to use this command.".to_string(),
+        ));
+    }
+
+    // Verify that the channel exists and is accessible
+    match channel_id.to_channel(&ctx).await {
+        Ok(_) => {
+            // Set the summary channel ID in the shared data
+            *ctx.data().summary_channel_id.lock().await = Some(channel_id);
+
+            // Save the updated data
+            if let Err(e) = save_data(ctx.data()).await {
+                eprintln!("Failed to save data after setting summary channel: {}", e);
+                ctx.say("Summary channel set, but there was an error saving the configuration.").await?;
+                return Ok(());
+            }
+
+            // Notify the user
+            ctx.say(format!("Summary channel set to <#{}>", channel_id)).await?;
+        }
+        Err(_) => {
+            return Err(BotError::UserError("Invalid channel or I don't have access to it.".to_string()));
+        }
+    }
+
+    Ok(())
+}
+
+#[poise::command(slash_command, category = "Configuration", ephemeral)]
+/// Set the time when daily summaries will be sent (24-hour format)
+async fn set_summary_time(
+    ctx: Context<'_>,
+    #[description = "Hour (0-23)"] hour: u32,
+    #[description = "Minute (0-59)"] minute: u32,
+) -> Result<(), Error> {
+    // Check if the user has permission to manage channels
+    require_manage_channels(ctx).await?;
+    
+    // Validate hour and minute
+    if hour > 23 || minute > 59 {
+        return Err(BotError::UserError(
+            "Invalid time. Hour must be between 0-23 and minute between 0-59.".to_string(),
+        ));
+    }
+    
+    // Set the summary time
+    *ctx.data().summary_time.lock().await = (hour, minute);
+    
+    // Save the updated data
+    if let Err(e) = save_data(ctx.data()).await {
+        eprintln!("Failed to save data after setting summary time: {}", e);
+        ctx.say("Summary time set, but there was an error saving the configuration.").await?;
+        return Ok(());
+    }
+    
+    ctx.say(format!("Summary time set to {:02}:{:02}", hour, minute)).await?;
+    
+    Ok(())
+}
+
+#[poise::command(slash_command, category = "Admin", ephemeral)]
+/// Manually trigger a standup summary (admin only)
+async fn trigger_summary(
+    ctx: Context<'_>,
+) -> Result<(), Error> {
+    // Check if the user has permission to manage channels
+    require_manage_channels(ctx).await?;
+    
+    ctx.say("Manually triggering standup summary...").await?;
+    
+    // Send the summary
+    match send_summary(&ctx.serenity_context().clone(), ctx.data()).await {
+        Ok(_) => {
+            // Update the last summary date
+            *ctx.data().last_summary_date.lock().await = Some(Local::now().date_naive());
+            if let Err(e) = save_data(ctx.data()).await {
+                eprintln!("Failed to save data after manual summary: {}", e);
+            }
+            
+            ctx.say("Summary sent successfully!").await?;
+        },
+        Err(e) => {
+            ctx.say(format!("Failed to send summary: {}", e)).await?;
+        }
+    }
+    
+    Ok(())
+}
+
+// Build the OAuth2 URL that installs the bot with every permission it needs
+fn invite_url(application_id: u64) -> String {
+    let permissions = serenity::Permissions::SEND_MESSAGES
+        | serenity::Permissions::EMBED_LINKS
+        | serenity::Permissions::ADD_REACTIONS
+        | serenity::Permissions::MANAGE_MESSAGES // Needed for pinning summaries
+        | serenity::Permissions::CREATE_PUBLIC_THREADS
+        | serenity::Permissions::READ_MESSAGE_HISTORY;
+
+    format!(
+        "https://discord.com/api/oauth2/authorize?client_id={}&permissions={}&scope=bot+applications.commands",
+        application_id,
+        permissions.bits()
+    )
+}
+
+#[poise::command(slash_command, category = "Admin", ephemeral)]
+/// Generate an invite link for adding the bot to another server (admin only)
+async fn generate_invite(
+    ctx: Context<'_>,
+) -> Result<(), Error> {
+    // Check if the user has permission to manage channels
+    require_manage_channels(ctx).await?;
+
+    let application_id = match ctx.data().config.application_id {
+        Some(id) => id,
+        None => {
+            return Err(BotError::UserError(
+                "APPLICATION_ID is not set, so I can't build an invite link. Add it to the bot's environment and restart.".to_string(),
+            ));
+        }
+    };
+
+    ctx.say(format!("[Click here to invite AgileMate to a server]({})", invite_url(application_id))).await?;
+
+    Ok(())
+}
+
+// Variables every custom entry format must include
+const REQUIRED_ENTRY_VARIABLES: [&str; 3] = ["{display_name}", "{did}", "{plan}"];
+
+// List the required variables a custom entry format is missing
+fn missing_entry_variables(template: &str) -> Vec<&'static str> {
+    REQUIRED_ENTRY_VARIABLES
+        .iter()
+        .copied()
+        .filter(|v| !template.contains(v))
+        .collect()
+}
+
+#[poise::command(slash_command, category = "Configuration", ephemeral)]
+/// Set a custom template for each user's section of the summary (admin only)
+async fn set_entry_format(
+    ctx: Context<'_>,
+    #[description = "Template, e.g. **{display_name}**: {did} / {plan} (use \\n for new lines)"] template: String,
+) -> Result<(), Error> {
+    // Check if the user has permission to manage channels
+    require_manage_channels(ctx).await?;
+
+    // Slash command options can't contain line breaks, so accept an escaped form instead
+    let template = template.replace("\\n", "\n");
+
+    let missing = missing_entry_variables(&template);
+    if !missing.is_empty() {
+        return Err(BotError::UserError(format!("The template must include {}.", missing.join(", "))));
+    }
+
+    *ctx.data().entry_format.lock().await = Some(template.clone());
+
+    // Save the updated data
+    if let Err(e) = save_data(ctx.data()).await {
+        eprintln!("Failed to save data after setting entry format: {}", e);
+        ctx.say("Entry format set, but there was an error saving the configuration.").await?;
+        return Ok(());
+    }
+
+    ctx.say(format!("Entry format set to:\n```\n{}\n```", template)).await?;
+
+    Ok(())
+}
+
+// Marker users add to a blocker once it has been dealt with
+const RESOLVED_MARKER: &str = "[RESOLVED]";
+
+#[poise::command(slash_command, category = "Admin", ephemeral)]
+/// List the blockers reported in pending standups (admin only)
+async fn blockers_report(
+    ctx: Context<'_>,
+    #[description = "Submission date (YYYY-MM-DD), defaults to today"] date: Option<String>,
+    #[description = "Include blockers marked [RESOLVED]"] include_resolved: Option<bool>,
+) -> Result<(), Error> {
+    // Check if the user has permission to manage channels
+    require_manage_channels(ctx).await?;
+
+    let date = match date {
+        Some(date) => match NaiveDate::parse_from_str(&date, "%Y-%m-%d") {
+            Ok(date) => date,
+            Err(_) => return Err(BotError::UserError("Invalid date. Use the format YYYY-MM-DD.".to_string())),
+        },
+        None => Local::now().date_naive(),
+    };
+    let include_resolved = include_resolved.unwrap_or(false);
+    let phrases = ctx.data().trivial_blocker_phrases.lock().await.clone();
+
+    let mut blocked: Vec<StandupEntry> = ctx
+        .data()
+        .standup_entries
+        .lock()
+        .await
+        .iter()
+        .filter(|e| e.timestamp.date_naive() == date)
+        .filter(|e| !is_trivial_blocker(&e.blockers, &phrases))
+        .filter(|e| include_resolved || !e.blockers.contains(RESOLVED_MARKER))
+        .cloned()
+        .collect();
+    // Most severe first, then by submission time
+    blocked.sort_by(|a, b| b.blocker_severity.cmp(&a.blocker_severity).then_with(|| a.cmp(b)));
+
+    if blocked.is_empty() {
+        ctx.say(format!("No open blockers reported on {}.", date)).await?;
+        return Ok(());
+    }
+
+    let mut report = format!("# Blockers for {}\n\n", date);
+    let lines: Vec<String> = blocked
+        .iter()
+        .map(|entry| {
+            let icon = entry.blocker_severity.map(|s| s.icon()).unwrap_or("⚪");
+            format!("- {} **{}**: {}\n", icon, entry.display_name, preview(&entry.blockers, 200))
+        })
+        .collect();
+    push_lines_within(&mut report, &lines, DISCORD_MESSAGE_LIMIT - LIST_FOOTER_RESERVE);
+    report.push_str(&format!("\n**Total:** {}", blocked.len()));
+
+    ctx.say(report).await?;
+
+    Ok(())
+}
+
+#[poise::command(slash_command, category = "Admin", ephemeral)]
+/// Check that the bot has the permissions it needs in the summary channel (admin only)
+async fn verify_permissions(
+    ctx: Context<'_>,
+) -> Result<(), Error> {
+    // Check if the user has permission to manage channels
+    require_manage_channels(ctx).await?;
+
+    let channel_id = match *ctx.data().summary_channel_id.lock().await {
+        Some(id) => id,
+        None => {
+            return Err(BotError::UserError(
+                "No summary channel set. Use /set_summary_channel first.".to_string(),
+            ));
+        }
+    };
+
+    let missing = missing_channel_permissions(ctx.serenity_context(), channel_id).await?;
+
+    let required = summary_channel_permissions().get_permission_names();
+    let table = required
+        .iter()
+        .map(|name| {
+            let status = if missing.contains(name) { "❌ Missing" } else { "✅ OK" };
+            format!("`{}` — {}", name, status)
+        })
+        .collect::<Vec<_>>()
+        .join("\n");
+    let colour = if missing.is_empty() {
+        serenity::Colour::DARK_GREEN
+    } else {
+        serenity::Colour::RED
+    };
+
+    ctx.send(|m| {
+        m.embed(|e| {
+            e.title("Summary channel permissions")
+                .description(format!("<#{}>", channel_id))
+                .field("Permission", table, false)
+                .colour(colour)
+        })
+    })
+    .await?;
+
+    Ok(())
+}
+
+#[poise::command(slash_command, category = "Configuration", ephemeral)]
+/// Turn the weekly summary channel permission check on or off (admin only)
+async fn toggle_perm_check(
+    ctx: Context<'_>,
+) -> Result<(), Error> {
+    // Check if the user has permission to manage channels
+    require_manage_channels(ctx).await?;
+
+    let enabled = {
+        let mut enabled = ctx.data().perm_check_enabled.lock().await;
+        *enabled = !*enabled;
+        *enabled
+    };
+
+    // Save the updated data
+    if let Err(e) = save_data(ctx.data()).await {
+        eprintln!("Failed to save data after toggling permission check: {}", e);
+        ctx.say("Permission check toggled, but there was an error saving the configuration.").await?;
+        return Ok(());
+    }
+
+    if enabled {
+        ctx.say("Weekly permission check enabled. I'll check the summary channel every Monday.").await?;
+    } else {
+        ctx.say("Weekly permission check disabled.").await?;
+    }
+
+    Ok(())
+}
+
+// Parse a schedule such as "daily", "daily 17:00", "weekly monday 09:00" or "biweekly monday 14:00 even".
+// The time is optional and is returned separately since it lives in `summary_time`.
+const SCHEDULE_USAGE: &str = "Use 'daily [HH:MM]', 'weekly <day> [HH:MM]' or 'biweekly <day> [HH:MM] <even|odd>'.";
+
+fn parse_schedule(input: &str) -> Result<(SummarySchedule, Option<(u32, u32)>), String> {
+    let parts: Vec<&str> = input.split_whitespace().collect();
+
+    let (schedule, time) = match parts.as_slice() {
+        [kind, rest @ ..] if kind.eq_ignore_ascii_case("daily") => (SummarySchedule::Daily, rest),
+        [kind, day, rest @ ..] if kind.eq_ignore_ascii_case("weekly") => {
+            let day = day
+                .parse::<Weekday>()
+                .map_err(|_| format!("'{}' is not a day of the week.", day))?;
+            (SummarySchedule::Weekly { day }, rest)
+        }
+        [kind, day, rest @ .., week] if kind.eq_ignore_ascii_case("biweekly") => {
+            let day = day
+                .parse::<Weekday>()
+                .map_err(|_| format!("'{}' is not a day of the week.", day))?;
+            let week = match week.to_lowercase().as_str() {
+                "even" => BiWeeklyParity::Even,
+                "odd" => BiWeeklyParity::Odd,
+                _ => return Err(format!("'{}' is not a week parity. Use 'even' or 'odd'.", week)),
+            };
+            (SummarySchedule::BiWeekly { day, week }, rest)
+        }
+        _ => return Err(SCHEDULE_USAGE.to_string()),
+    };
+
+    let time = match time {
+        [] => None,
+        [time] => Some(parse_time(time).ok_or_else(|| format!("'{}' is not a valid HH:MM time.", time))?),
+        _ => return Err(format!("Too many values. {}", SCHEDULE_USAGE)),
+    };
+
+    Ok((schedule, time))
+}
+
+// Parse a 24-hour HH:MM time
+fn parse_time(input: &str) -> Option<(u32, u32)> {
+    let (hour, minute) = input.split_once(':')?;
+    let hour = hour.parse::<u32>().ok()?;
+    let minute = minute.parse::<u32>().ok()?;
+    (hour <= 23 && minute <= 59).then_some((hour, minute))
+}
+
+#[poise::command(slash_command, category = "Configuration", ephemeral)]
+/// Set how often summaries are sent, e.g. "daily 17:00" or "biweekly monday 09:00 even" (admin only)
+async fn set_schedule(
+    ctx: Context<'_>,
+    #[description = "daily [HH:MM], weekly <day> [HH:MM] or biweekly <day> [HH:MM] <even|odd>"] schedule: String,
+) -> Result<(), Error> {
+    // Check if the user has permission to manage channels
+    require_manage_channels(ctx).await?;
+
+    let (schedule, time) =
+        parse_schedule(&schedule).map_err(|e| BotError::UserError(format!("Invalid schedule. {}", e)))?;
+
+    *ctx.data().summary_schedule.lock().await = schedule;
+    if let Some(time) = time {
+        *ctx.data().summary_time.lock().await = time;
+    }
+
+    // Save the updated data
+    if let Err(e) = save_data(ctx.data()).await {
+        eprintln!("Failed to save data after setting schedule: {}", e);
+        ctx.say("Schedule set, but there was an error saving the configuration.").await?;
+        return Ok(());
+    }
+
+    let summary_time = *ctx.data().summary_time.lock().await;
+    let next = match schedule.next_fire(Local::now(), summary_time) {
+        Some(next) => format!(" Next summary: {}.", next.format("%A %Y-%m-%d %H:%M")),
+        None => String::new(),
+    };
+    ctx.say(format!(
+        "{} summaries will be sent at {:02}:{:02}.{}",
+        schedule.period_name(),
+        summary_time.0,
+        summary_time.1,
+        next
+    ))
+    .await?;
+
+    Ok(())
+}
+
+#[poise::command(slash_command, category = "Configuration", ephemeral)]
+/// Set how users are ordered in the summary (admin only)
+async fn set_summary_sort_order(
+    ctx: Context<'_>,
+    #[description = "Order of users in the summary"] order: SortOrder,
+) -> Result<(), Error> {
+    // Check if the user has permission to manage channels
+    require_manage_channels(ctx).await?;
+
+    *ctx.data().summary_sort_order.lock().await = order;
+
+    // Save the updated data
+    if let Err(e) = save_data(ctx.data()).await {
+        eprintln!("Failed to save data after setting summary sort order: {}", e);
+        ctx.say("Summary sort order set, but there was an error saving the configuration.").await?;
+        return Ok(());
+    }
+
+    ctx.say(format!("Summary sort order set to {}", order.name())).await?;
+
+    Ok(())
+}
+
+// Largest avatar image Discord accepts
+const MAX_AVATAR_BYTES: usize = 8 * 1024 * 1024;
+
+// How long downloading a new avatar may take before giving up
+const AVATAR_DOWNLOAD_TIMEOUT: Duration = Duration::from_secs(15);
+
+// Minimum time between avatar changes
+const AVATAR_CHANGE_COOLDOWN_MINUTES: i64 = 60;
+
+// Work out the MIME type of a supported avatar image from its leading bytes
+fn avatar_mime_type(bytes: &[u8]) -> Option<&'static str> {
+    if bytes.starts_with(b"\x89PNG\r\n\x1a\n") {
+        Some("image/png")
+    } else if bytes.starts_with(&[0xFF, 0xD8, 0xFF]) {
+        Some("image/jpeg")
+    } else if bytes.starts_with(b"GIF87a") || bytes.starts_with(b"GIF89a") {
+        Some("image/gif")
+    } else if bytes.len() >= 12 && &bytes[0..4] == b"RIFF" && &bytes[8..12] == b"WEBP" {
+        Some("image/webp")
+    } else {
+        None
+    }
+}
+
+// Download an avatar image and encode it as a data URI for Discord
+async fn download_avatar(url: &str) -> Result<String, String> {
+    let parsed = reqwest::Url::parse(url).map_err(|_| "That isn't a valid URL.".to_string())?;
+    if parsed.scheme() != "https" {
+        return Err("The image URL must use https://.".to_string());
+    }
+
+    let client = reqwest::Client::builder()
+        .timeout(AVATAR_DOWNLOAD_TIMEOUT)
+        .build()
+        .map_err(|e| format!("Failed to download the image: {}", e))?;
+    let mut response = client
+        .get(parsed)
+        .send()
+        .await
+        .and_then(|r| r.error_for_status())
+        .map_err(|e| format!("Failed to download the image: {}", e))?;
+
+    if response.content_length().is_some_and(|len| len > MAX_AVATAR_BYTES as u64) {
+        return Err("The image is larger than 8MB.".to_string());
+    }
+
+    // Read in chunks so a server that doesn't send Content-Length can't make us buffer more than the limit
+    let mut bytes = Vec::new();
+    while let Some(chunk) = response
+        .chunk()
+        .await
+        .map_err(|e| format!("Failed to download the image: {}", e))?
+    {
+        bytes.extend_from_slice(&chunk);
+        if bytes.len() > MAX_AVATAR_BYTES {
+            return Err("The image is larger than 8MB.".to_string());
+        }
+    }
+
+    let mime_type = avatar_mime_type(&bytes).ok_or("The image must be a PNG, JPG, GIF or WEBP.")?;
+
+    Ok(format!(
+        "data:{};base64,{}",
+        mime_type,
+        base64::engine::general_purpose::STANDARD.encode(&bytes)
+    ))
+}
+
+#[poise::command(slash_command, category = "Configuration", ephemeral)]
+/// Change the bot's avatar for every server it's in (server owner only)
+async fn set_bot_avatar(
+    ctx: Context<'_>,
+    #[description = "HTTPS URL of a PNG, JPG, GIF or WEBP image under 8MB"] url: String,
+) -> Result<(), Error> {
+    // Only the server owner may change the bot's global avatar
+    let is_owner = ctx
+        .partial_guild()
+        .await
+        .is_some_and(|guild| guild.owner_id == ctx.author().id);
+    if !is_owner {
+        return Err(BotError::PermissionDenied(
+            "Only the server owner can change the bot's avatar.".to_string(),
+        ));
+    }
+
+    // Downloading and uploading the image can take longer than Discord waits for a reply
+    ctx.defer_ephemeral().await?;
+
+    // Allow at most one change per hour
+    if let Some(last_change) = *ctx.data().last_avatar_change.lock().await {
+        let next_allowed = last_change + chrono::Duration::minutes(AVATAR_CHANGE_COOLDOWN_MINUTES);
+        if Local::now() < next_allowed {
+            return Err(BotError::UserError(format!(
+                "The avatar was changed recently. Try again after {}.",
+                next_allowed.format("%H:%M")
+            )));
+        }
+    }
+
+    let avatar = download_avatar(&url).await.map_err(BotError::UserError)?;
+
+    let mut current_user = ctx.serenity_context().cache.current_user();
+    if let Err(e) = current_user.edit(ctx, |p| p.avatar(Some(&avatar))).await {
+        eprintln!("Failed to update bot avatar: {:?}", e);
+        ctx.say("Discord rejected the new avatar. Please try again later.").await?;
+        return Ok(());
+    }
+
+    println!("Bot avatar changed to {} by {} ({})", url, ctx.author().name, ctx.author().id);
+    *ctx.data().last_avatar_change.lock().await = Some(Local::now());
+
+    // Save the updated data
+    if let Err(e) = save_data(ctx.data()).await {
+        eprintln!("Failed to save data after changing avatar: {}", e);
+    }
+
+    ctx.say("Avatar updated. Note that the avatar is global, so this change applies to every server the bot is in.").await?;
+
+    Ok(())
+}
+
+#[poise::command(slash_command, category = "Configuration", ephemeral)]
+/// Set a channel that gets a short notification for every standup submission (admin only)
+async fn set_live_feed_channel(
+    ctx: Context<'_>,
+    #[description = "The channel for submission notifications"] channel_id: serenity::ChannelId,
+) -> Result<(), Error> {
+    // Check if the user has permission to manage channels
+    require_manage_channels(ctx).await?;
+
+    // Verify that the channel exists and is accessible
+    if channel_id.to_channel(&ctx).await.is_err() {
+        return Err(BotError::UserError("Invalid channel or I don't have access to it.".to_string()));
+    }
+
+    *ctx.data().live_feed_channel_id.lock().await = Some(channel_id);
+
+    // Save the updated data
+    if let Err(e) = save_data(ctx.data()).await {
+        eprintln!("Failed to save data after setting live feed channel: {}", e);
+        ctx.say("Live feed channel set, but there was an error saving the configuration.").await?;
+        return Ok(());
+    }
+
+    ctx.say(format!("Live feed channel set to <#{}>", channel_id)).await?;
+
+    Ok(())
+}
+
+#[poise::command(slash_command, category = "Configuration", ephemeral)]
+/// Stop posting submission notifications to the live feed channel (admin only)
+async fn clear_live_feed_channel(
+    ctx: Context<'_>,
+) -> Result<(), Error> {
+    // Check if the user has permission to manage channels
+    require_manage_channels(ctx).await?;
+
+    *ctx.data().live_feed_channel_id.lock().await = None;
+
+    // Save the updated data
+    if let Err(e) = save_data(ctx.data()).await {
+        eprintln!("Failed to save data after clearing live feed channel: {}", e);
+        ctx.say("Live feed channel cleared, but there was an error saving the configuration.").await?;
+        return Ok(());
+    }
+
+    ctx.say("Live feed channel cleared.").await?;
+
+    Ok(())
+}
+
+#[poise::command(slash_command, category = "Configuration", ephemeral)]
+/// Set a channel for the bot's warnings and notices, separate from summaries (admin only)
+async fn set_announcement_channel(
+    ctx: Context<'_>,
+    #[description = "The channel for bot announcements"] channel_id: serenity::ChannelId,
+) -> Result<(), Error> {
+    // Check if the user has permission to manage channels
+    require_manage_channels(ctx).await?;
+
+    // Verify that the channel exists and is accessible
+    if channel_id.to_channel(&ctx).await.is_err() {
+        return Err(BotError::UserError("Invalid channel or I don't have access to it.".to_string()));
+    }
+
+    *ctx.data().announcement_channel_id.lock().await = Some(channel_id);
+
+    // Save the updated data
+    if let Err(e) = save_data(ctx.data()).await {
+        eprintln!("Failed to save data after setting announcement channel: {}", e);
+        ctx.say("Announcement channel set, but there was an error saving the configuration.").await?;
+        return Ok(());
+    }
+
+    ctx.say(format!("Announcement channel set to <#{}>", channel_id)).await?;
+
+    Ok(())
+}
+
+#[poise::command(slash_command, category = "Configuration", ephemeral)]
+/// Post the bot's warnings and notices in the summary channel again (admin only)
+async fn clear_announcement_channel(
+    ctx: Context<'_>,
+) -> Result<(), Error> {
+    // Check if the user has permission to manage channels
+    require_manage_channels(ctx).await?;
+
+    *ctx.data().announcement_channel_id.lock().await = None;
+
+    // Save the updated data
+    if let Err(e) = save_data(ctx.data()).await {
+        eprintln!("Failed to save data after clearing announcement channel: {}", e);
+        ctx.say("Announcement channel cleared, but there was an error saving the configuration.").await?;
+        return Ok(());
+    }
+
+    ctx.say("Announcement channel cleared. Announcements will go to the summary channel.").await?;
+
+    Ok(())
+}
+
+// Wall-clock timings of a repeated operation, in milliseconds
+#[derive(Debug, Clone, Copy)]
+struct LatencyStats {
+    min_ms: f64,
+    mean_ms: f64,
+    max_ms: f64,
+}
+
+// Run an async operation `n` times and measure how long each run takes
+async fn measure_latency<F, Fut>(mut f: F, n: usize) -> LatencyStats
+where
+    F: FnMut() -> Fut,
+    Fut: std::future::Future,
+{
+    let mut timings = Vec::with_capacity(n);
+    for _ in 0..n {
+        let start = Instant::now();
+        f().await;
+        timings.push(start.elapsed().as_secs_f64() * 1000.0);
+    }
+
+    let min_ms = timings.iter().copied().fold(f64::INFINITY, f64::min);
+    let max_ms = timings.iter().copied().fold(0.0, f64::max);
+    let mean_ms = timings.iter().sum::<f64>() / timings.len().max(1) as f64;
+
+    LatencyStats {
+        min_ms: if timings.is_empty() { 0.0 } else { min_ms },
+        mean_ms,
+        max_ms,
+    }
+}
+
+// Number of times each storage operation runs in /benchmark
+const BENCHMARK_RUNS: usize = 10;
+
+#[poise::command(slash_command, category = "Admin", ephemeral)]
+/// Measure how long saving and loading the bot's data takes (admin only)
+async fn benchmark(
+    ctx: Context<'_>,
+) -> Result<(), Error> {
+    // Check if the user has permission to manage channels
+    require_manage_channels(ctx).await?;
+
+    ctx.defer_ephemeral().await?;
+
+    // Saving rewrites the live data that's already persisted, so this is safe in production
+    let data = ctx.data();
+    let save_stats = measure_latency(
+        || async {
+            if let Err(e) = save_data(data).await {
+                eprintln!("Failed to save data during benchmark: {}", e);
+            }
+        },
+        BENCHMARK_RUNS,
+    )
+    .await;
+
+    // Loading builds a throwaway copy of the data; the live state isn't replaced
+    let load_stats = measure_latency(|| load_data((*data.config).clone()), BENCHMARK_RUNS).await;
+
+    let data_path = data.config.storage_format.path();
+    let file_size = match fs::metadata(data_path) {
+        Ok(metadata) => format!("{:.1} KB", metadata.len() as f64 / 1024.0),
+        Err(_) => "unknown".to_string(),
+    };
+
+    ctx.say(format!(
+        "**Storage benchmark** ({} runs each)\n\
+         **save_data:** min {:.2} ms · mean {:.2} ms · max {:.2} ms\n\
+         **load_data:** min {:.2} ms · mean {:.2} ms · max {:.2} ms\n\
+         **{} size:** {}",
+        BENCHMARK_RUNS,
+        save_stats.min_ms,
+        save_stats.mean_ms,
+        save_stats.max_ms,
+        load_stats.min_ms,
+        load_stats.mean_ms,
+        load_stats.max_ms,
+        data_path,
+        file_size
+    ))
+    .await?;
+
+    Ok(())
+}
+
+#[poise::command(slash_command, category = "Info", ephemeral)]
+/// Show the bot's current configuration and health
+async fn status(
+    ctx: Context<'_>,
+) -> Result<(), Error> {
+    let data = ctx.data();
+
+    let summary_channel = match *data.summary_channel_id.lock().await {
+        Some(id) => format!("<#{}>", id),
+        None => "Not set".to_string(),
+    };
+    let summary_image = match data.summary_image_url.lock().await.as_ref() {
+        Some(url) => format!("<{}>", url),
+        None => "None".to_string(),
+    };
+    let announcement_channel = match *data.announcement_channel_id.lock().await {
+        Some(id) => format!("<#{}>", id),
+        None => "Summary channel".to_string(),
+    };
+    let (hour, minute) = *data.summary_time.lock().await;
+    let schedule = data.summary_schedule.lock().await.describe((hour, minute));
+    let pending_entries = data.standup_entries.lock().await.len();
+    let rate_limit_hits = data.rate_limit_hits.load(Ordering::Relaxed);
+    let scheduler_restarts = data.scheduler_restarts.load(Ordering::Relaxed);
+    let shard_id = data.shard_id.load(Ordering::Relaxed);
+    let total_shards = data.total_shards.load(Ordering::Relaxed);
+
+    ctx.say(format!(
+        "**Summary channel:** {}\n\
+         **Announcement channel:** {}\n\
+         **Summary image:** {}\n\
+         **Schedule:** {}\n\
+         **Pending entries:** {}\n\
+         **Rate limit hits:** {}\n\
+         **Scheduler restarts:** {}\n\
+         **Shard:** {} of {}",
+        summary_channel,
+        announcement_channel,
+        summary_image,
+        schedule,
+        pending_entries,
+        rate_limit_hits,
+        scheduler_restarts,
+        shard_id + 1,
+        total_shards
+    ))
+    .await?;
+
+    Ok(())
+}
+
+#[poise::command(slash_command, category = "Standup", ephemeral)]
+/// Save your plan now to use in your next standup
+async fn plan_for_tomorrow(
+    ctx: Context<'_>,
+    #[description = "What you plan to do next"] plan: String,
+) -> Result<(), Error> {
+    let user_id = ctx.author().id.to_string();
+    let tomorrow_plan = TomorrowPlan {
+        plan,
+        saved_on: Local::now().date_naive(),
+    };
+    ctx.data().tomorrow_plans.insert(user_id, tomorrow_plan);
+
+    // Save the updated data
+    if let Err(e) = save_data(ctx.data()).await {
+        eprintln!("Failed to save data after saving tomorrow's plan: {}", e);
+        ctx.say("Your plan has been saved, but there was an error saving the data.").await?;
+        return Ok(());
+    }
+
+    ctx.say(format!(
+        "Your plan has been saved. Leave the plan empty in your next /standup to use it. It expires after {} days.",
+        TOMORROW_PLAN_EXPIRY_DAYS
+    ))
+    .await?;
+
+    Ok(())
+}
+
+#[poise::command(slash_command, category = "Standup", ephemeral)]
+/// Discard the plan you saved with /plan_for_tomorrow
+async fn clear_tomorrow_plan(
+    ctx: Context<'_>,
+) -> Result<(), Error> {
+    let removed = ctx
+        .data()
+        .tomorrow_plans
+        .remove(&ctx.author().id.to_string());
+
+    if removed.is_none() {
+        ctx.say("You don't have a saved plan.").await?;
+        return Ok(());
+    }
+
+    // Save the updated data
+    if let Err(e) = save_data(ctx.data()).await {
+        eprintln!("Failed to save data after clearing tomorrow's plan: {}", e);
+        ctx.say("Your saved plan has been cleared, but there was an error saving the data.").await?;
+        return Ok(());
+    }
+
+    ctx.say("Your saved plan has been cleared.").await?;
+
+    Ok(())
+}
+
+#[poise::command(slash_command, category = "Configuration", ephemeral)]
+/// Treat a blockers answer as meaning "no blockers" (admin only)
+async fn add_trivial_phrase(
+    ctx: Context<'_>,
+    #[description = "Phrase such as \"none\" or \"n/a\""] phrase: String,
+) -> Result<(), Error> {
+    // Check if the user has permission to manage channels
+    require_manage_channels(ctx).await?;
+
+    let phrase = normalize_phrase(&phrase);
+    if phrase.is_empty() {
+        return Err(BotError::UserError("The phrase can't be empty.".to_string()));
+    }
+
+    if !ctx.data().trivial_blocker_phrases.lock().await.insert(phrase.clone()) {
+        ctx.say(format!("\"{}\" is already treated as no blockers.", phrase)).await?;
+        return Ok(());
+    }
+
+    // Save the updated data
+    if let Err(e) = save_data(ctx.data()).await {
+        eprintln!("Failed to save data after adding trivial phrase: {}", e);
+        ctx.say("Phrase added, but there was an error saving the configuration.").await?;
+        return Ok(());
+    }
+
+    ctx.say(format!("\"{}\" will now be treated as no blockers.", phrase)).await?;
+
+    Ok(())
+}
+
+#[poise::command(slash_command, category = "Configuration", ephemeral)]
+/// Stop treating a blockers answer as meaning "no blockers" (admin only)
+async fn remove_trivial_phrase(
+    ctx: Context<'_>,
+    #[description = "Phrase to remove"] phrase: String,
+) -> Result<(), Error> {
+    // Check if the user has permission to manage channels
+    require_manage_channels(ctx).await?;
+
+    let phrase = normalize_phrase(&phrase);
+    if !ctx.data().trivial_blocker_phrases.lock().await.remove(&phrase) {
+        ctx.say(format!("\"{}\" isn't in the list of no-blocker phrases.", phrase)).await?;
+        return Ok(());
+    }
+
+    // Save the updated data
+    if let Err(e) = save_data(ctx.data()).await {
+        eprintln!("Failed to save data after removing trivial phrase: {}", e);
+        ctx.say("Phrase removed, but there was an error saving the configuration.").await?;
+        return Ok(());
+    }
+
+    ctx.say(format!("\"{}\" will now be treated as a real blocker.", phrase)).await?;
+
+    Ok(())
+}
+
+#[poise::command(slash_command, category = "Info", ephemeral)]
+/// List the blockers answers treated as meaning "no blockers"
+async fn list_trivial_phrases(
+    ctx: Context<'_>,
+) -> Result<(), Error> {
+    let mut phrases: Vec<String> = ctx
+        .data()
+        .trivial_blocker_phrases
+        .lock()
+        .await
+        .iter()
+        .cloned()
+        .collect();
+    phrases.sort();
+
+    if phrases.is_empty() {
+        ctx.say("No phrases are configured. Only empty answers count as no blockers.").await?;
+        return Ok(());
+    }
+
+    let list = phrases
+        .iter()
+        .map(|p| format!("- `{}`", p))
+        .collect::<Vec<_>>()
+        .join("\n");
+    ctx.say(format!("These answers are treated as no blockers:\n{}", list)).await?;
+
+    Ok(())
+}
+
+#[poise::command(slash_command, category = "Configuration", ephemeral)]
+/// Mark blockers that mention a keyword with a severity (admin only)
+async fn add_blocker_keyword(
+    ctx: Context<'_>,
+    #[description = "Severity for blockers mentioning the keyword"] severity: BlockerSeverity,
+    #[description = "Word or phrase such as \"outage\""] keyword: String,
+) -> Result<(), Error> {
+    // Check if the user has permission to manage channels
+    require_manage_channels(ctx).await?;
+
+    let keyword = normalize_phrase(&keyword);
+    if keyword.is_empty() {
+        return Err(BotError::UserError("The keyword can't be empty.".to_string()));
+    }
+
+    {
+        let mut keywords = ctx.data().blocker_keywords.lock().await;
+        // A keyword belongs to one severity only, so adding it again moves it
+        for list in keywords.values_mut() {
+            list.retain(|k| *k != keyword);
+        }
+        keywords.entry(severity).or_default().push(keyword.clone());
+    }
+
+    // Save the updated data
+    if let Err(e) = save_data(ctx.data()).await {
+        eprintln!("Failed to save data after adding blocker keyword: {}", e);
+        ctx.say("Keyword added, but there was an error saving the configuration.").await?;
+        return Ok(());
+    }
+
+    ctx.say(format!("Blockers mentioning \"{}\" will be marked {}.", keyword, severity.name())).await?;
+
+    Ok(())
+}
+
+#[poise::command(slash_command, category = "Configuration", ephemeral)]
+/// Stop using a keyword to detect blocker severity (admin only)
+async fn remove_blocker_keyword(
+    ctx: Context<'_>,
+    #[description = "Keyword to remove"] keyword: String,
+) -> Result<(), Error> {
+    // Check if the user has permission to manage channels
+    require_manage_channels(ctx).await?;
+
+    let keyword = normalize_phrase(&keyword);
+    let removed = {
+        let mut keywords = ctx.data().blocker_keywords.lock().await;
+        let mut removed = false;
+        for list in keywords.values_mut() {
+            let before = list.len();
+            list.retain(|k| *k != keyword);
+            removed |= list.len() < before;
+        }
+        removed
+    };
+
+    if !removed {
+        ctx.say(format!("\"{}\" isn't a blocker keyword.", keyword)).await?;
+        return Ok(());
+    }
+
+    // Save the updated data
+    if let Err(e) = save_data(ctx.data()).await {
+        eprintln!("Failed to save data after removing blocker keyword: {}", e);
+        ctx.say("Keyword removed, but there was an error saving the configuration.").await?;
+        return Ok(());
+    }
+
+    ctx.say(format!("\"{}\" no longer affects blocker severity.", keyword)).await?;
+
+    Ok(())
+}
+
+#[poise::command(slash_command, category = "Info", ephemeral)]
+/// List the keywords used to detect blocker severity
+async fn list_blocker_keywords(
+    ctx: Context<'_>,
+) -> Result<(), Error> {
+    let keywords = ctx.data().blocker_keywords.lock().await.clone();
+
+    let mut severities: Vec<_> = keywords.into_iter().filter(|(_, list)| !list.is_empty()).collect();
+    if severities.is_empty() {
+        ctx.say("No blocker keywords are configured.").await?;
+        return Ok(());
+    }
+    severities.sort_by_key(|(severity, _)| std::cmp::Reverse(*severity));
+
+    let list = severities
+        .iter()
+        .map(|(severity, list)| format!("{} **{}:** {}", severity.icon(), severity.name(), list.join(", ")))
+        .collect::<Vec<_>>()
+        .join("\n");
+    ctx.say(format!("Blocker keywords by severity:\n{}", list)).await?;
+
+    Ok(())
+}
+
+#[poise::command(slash_command, category = "Standup", ephemeral)]
+/// Skip today's standup
+async fn skip_today(
+    ctx: Context<'_>,
+    #[description = "Why you're skipping"] reason: Option<String>,
+) -> Result<(), Error> {
+    let user = ctx.author();
+
+    let display_name = author_display_name(ctx).await;
+
+    let needs_approval = *ctx.data().require_skip_approval.lock().await;
+    let today = Local::now().date_naive();
+    let skip = SkipEntry {
+        user_id: user.id.to_string(),
+        display_name,
+        date: today,
+        reason,
+        approved: !needs_approval,
+    };
+
+    {
+        let mut skip_log = ctx.data().skip_log.lock().await;
+
+        // Replace any earlier skip for today
+        skip_log.retain(|s| !(s.user_id == skip.user_id && s.date == today));
+        skip_log.push(skip);
+    }
+
+    let reply = if needs_approval {
+        "Your skip has been recorded and is waiting for approval from an admin."
+    } else {
+        "You're skipping today's standup. Enjoy your day!"
+    };
+
+    // Save the updated data
+    if let Err(e) = save_data(ctx.data()).await {
+        eprintln!("Failed to save data after skipping standup: {}", e);
+        ctx.say(format!("{} (There was an error saving the data.)", reply)).await?;
+        return Ok(());
+    }
+
+    ctx.say(reply).await?;
+
+    Ok(())
+}
+
+#[poise::command(slash_command, category = "Admin", ephemeral)]
+/// Approve a user's skip for today (admin only)
+async fn approve_skip(
+    ctx: Context<'_>,
+    #[description = "User whose skip to approve"] user: serenity::User,
+) -> Result<(), Error> {
+    // Check if the user has permission to manage channels
+    require_manage_channels(ctx).await?;
+
+    let today = Local::now().date_naive();
+    let approved = {
+        let mut skip_log = ctx.data().skip_log.lock().await;
+        match skip_log
+            .iter_mut()
+            .find(|s| s.user_id == user.id.to_string() && s.date == today)
+        {
+            Some(skip) => {
+                skip.approved = true;
+                true
+            }
+            None => false,
+        }
+    };
+
+    if !approved {
+        return Err(BotError::UserError(format!("{} hasn't asked to skip today.", user.name)));
+    }
+
+    // Save the updated data
+    if let Err(e) = save_data(ctx.data()).await {
+        eprintln!("Failed to save data after approving skip: {}", e);
+        ctx.say("Skip approved, but there was an error saving the data.").await?;
+        return Ok(());
+    }
+
+    ctx.say(format!("Approved {}'s skip for today.", user.name)).await?;
+
+    Ok(())
+}
+
+#[poise::command(slash_command, category = "Configuration", ephemeral)]
+/// Turn approval of skips on or off (admin only)
+async fn toggle_skip_approval(
+    ctx: Context<'_>,
+) -> Result<(), Error> {
+    // Check if the user has permission to manage channels
+    require_manage_channels(ctx).await?;
+
+    let required = {
+        let mut required = ctx.data().require_skip_approval.lock().await;
+        *required = !*required;
+        *required
+    };
+
+    // Save the updated data
+    if let Err(e) = save_data(ctx.data()).await {
+        eprintln!("Failed to save data after toggling skip approval: {}", e);
+        ctx.say("Skip approval toggled, but there was an error saving the configuration.").await?;
+        return Ok(());
+    }
+
+    if required {
+        ctx.say("Skips now need approval with /approve_skip.").await?;
+    } else {
+        ctx.say("Skips no longer need approval.").await?;
+    }
+
+    Ok(())
+}
+
+// Longest footer allowed on summaries
+const MAX_FOOTER_LENGTH: usize = 200;
+
+// Markdown that would break the footer line or ping the whole server
+const FORBIDDEN_FOOTER_MARKDOWN: [&str; 4] = ["```", "@everyone", "@here", "-#"];
+
+#[poise::command(slash_command, category = "Configuration", ephemeral)]
+/// Add a custom footer to every summary; supports {date} and {count} (admin only)
+async fn set_summary_footer(
+    ctx: Context<'_>,
+    #[description = "Footer text, up to 200 characters"] text: String,
+) -> Result<(), Error> {
+    // Check if the user has permission to manage channels
+    require_manage_channels(ctx).await?;
+
+    let text = text.trim().to_string();
+    if text.is_empty() || text.chars().count() > MAX_FOOTER_LENGTH {
+        return Err(BotError::UserError(format!(
+            "The footer must be between 1 and {} characters.",
+            MAX_FOOTER_LENGTH
+        )));
+    }
+    if let Some(forbidden) = FORBIDDEN_FOOTER_MARKDOWN.iter().find(|m| text.contains(*m)) {
+        return Err(BotError::UserError(format!("The footer can't contain `{}`.", forbidden)));
+    }
+
+    *ctx.data().summary_footer.lock().await = Some(text.clone());
+
+    // Save the updated data
+    if let Err(e) = save_data(ctx.data()).await {
+        eprintln!("Failed to save data after setting summary footer: {}", e);
+        ctx.say("Summary footer set, but there was an error saving the configuration.").await?;
+        return Ok(());
+    }
+
+    ctx.say(format!("Summary footer set to: {}", text)).await?;
+
+    Ok(())
+}
+
+#[poise::command(slash_command, category = "Configuration", ephemeral)]
+/// Remove the custom footer from summaries (admin only)
+async fn clear_summary_footer(
+    ctx: Context<'_>,
+) -> Result<(), Error> {
+    // Check if the user has permission to manage channels
+    require_manage_channels(ctx).await?;
+
+    *ctx.data().summary_footer.lock().await = None;
+
+    // Save the updated data
+    if let Err(e) = save_data(ctx.data()).await {
+        eprintln!("Failed to save data after clearing summary footer: {}", e);
+        ctx.say("Summary footer cleared, but there was an error saving the configuration.").await?;
+        return Ok(());
+    }
+
+    ctx.say("Summary footer cleared.").await?;
+
+    Ok(())
+}
+
+// Custom ID prefix for summary time poll buttons; the option index follows it
+const VOTE_BUTTON_PREFIX: &str = "standup_vote:";
+
+// How long a summary time poll runs unless told otherwise
+const DEFAULT_VOTE_DURATION_HOURS: u32 = 24;
+
+// Longest a summary time poll can run
+const MAX_VOTE_DURATION_HOURS: u32 = 168;
+
+// Record a vote from one of the poll's buttons
+async fn handle_vote_button(
+    ctx: &serenity::Context,
+    data: &Data,
+    component: &serenity::MessageComponentInteraction,
+) -> Result<(), Error> {
+    let option = component.data.custom_id[VOTE_BUTTON_PREFIX.len()..].parse::<usize>().ok();
+    let user_id = component.user.id.to_string();
+
+    let (reply, recorded) = {
+        let mut active_vote = data.active_vote.lock().await;
+        match (active_vote.as_mut(), option) {
+            (Some(poll), Some(option))
+                if poll.message_id == component.message.id && option < poll.options.len() =>
+            {
+                let (hour, minute) = poll.options[option];
+                match poll.votes.entry(user_id) {
+                    Entry::Occupied(_) => ("You've already voted in this poll.".to_string(), false),
+                    Entry::Vacant(vote) => {
+                        vote.insert(option);
+                        (format!("Your vote for {:02}:{:02} has been counted.", hour, minute), true)
+                    }
+                }
+            }
+            _ => ("This poll has closed.".to_string(), false),
+        }
+    };
+
+    if recorded {
+        if let Err(e) = save_data(data).await {
+            eprintln!("Failed to save data after recording vote: {}", e);
+        }
+    }
+
+    component
+        .create_interaction_response(ctx, |r| {
+            r.kind(serenity::InteractionResponseType::ChannelMessageWithSource)
+                .interaction_response_data(|d| d.content(reply).ephemeral(true))
+        })
+        .await?;
+
+    Ok(())
+}
+
+// Tally the summary time poll once it ends, announce the result and apply it if requested
+async fn finish_vote_if_due(ctx: &serenity::Context, data: &Data, now: DateTime<Local>) {
+    let poll = {
+        let mut active_vote = data.active_vote.lock().await;
+        match active_vote.as_ref() {
+            Some(poll) if poll.ends_at <= now => active_vote.take(),
+            _ => None,
+        }
+    };
+    let poll = match poll {
+        Some(poll) => poll,
+        None => return,
+    };
+
+    let mut counts = vec![0usize; poll.options.len()];
+    for option in poll.votes.values() {
+        if let Some(count) = counts.get_mut(*option) {
+            *count += 1;
+        }
+    }
+
+    // A tie or an empty poll has no winner
+    let top = counts.iter().copied().max().unwrap_or(0);
+    let leaders: Vec<usize> = (0..counts.len()).filter(|i| counts[*i] == top).collect();
+    let winner = match leaders.as_slice() {
+        [winner] if top > 0 => Some(poll.options[*winner]),
+        _ => None,
+    };
+
+    let tally = poll
+        .options
+        .iter()
+        .zip(&counts)
+        .map(|((hour, minute), count)| format!("- {:02}:{:02} — {} vote(s)", hour, minute, count))
+        .collect::<Vec<_>>()
+        .join("\n");
+    let mut result = format!("**Summary time poll closed.**\n{}\n", tally);
+
+    match winner {
+        Some((hour, minute)) if poll.auto_apply => {
+            *data.summary_time.lock().await = (hour, minute);
+            result.push_str(&format!("Summaries will now be sent at {:02}:{:02}.", hour, minute));
+        }
+        Some((hour, minute)) => {
+            result.push_str(&format!("The team picked {:02}:{:02}. Use /set_summary_time to apply it.", hour, minute));
+        }
+        None => result.push_str("There was no clear winner, so the summary time is unchanged."),
+    }
+
+    println!(
+        "Summary time poll started by {} closed with {} votes; winner: {:?}; applied: {}",
+        poll.started_by,
+        poll.votes.len(),
+        winner,
+        poll.auto_apply && winner.is_some()
+    );
+
+    // Remove the buttons so nobody votes in a closed poll
+    if let Err(e) = poll
+        .channel_id
+        .edit_message(ctx, poll.message_id, |m| m.components(|c| c))
+        .await
+    {
+        eprintln!("Failed to close poll buttons: {:?}", e);
+    }
+
+    match poll.channel_id.say(ctx, result).await {
+        Ok(message) => log_bot_message(data, &message, MessageType::Poll, 0).await,
+        Err(e) => eprintln!("Failed to post poll result: {:?}", e),
+    }
+
+    if let Err(e) = save_data(data).await {
+        eprintln!("Failed to save data after closing poll: {}", e);
+    }
+}
+
+#[poise::command(slash_command, category = "Admin", ephemeral)]
+/// Ask the team to vote on the summary time (admin only)
+async fn vote_on_standup_time(
+    ctx: Context<'_>,
+    #[description = "First option (HH:MM)"] option1: String,
+    #[description = "Second option (HH:MM)"] option2: String,
+    #[description = "Third option (HH:MM)"] option3: Option<String>,
+    #[description = "How long the poll runs, in hours (default 24)"] duration_hours: Option<u32>,
+    #[description = "Set the summary time to the winner automatically (default true)"] auto_apply: Option<bool>,
+) -> Result<(), Error> {
+    // Check if the user has permission to manage channels
+    require_manage_channels(ctx).await?;
+
+    let mut options = Vec::new();
+    for option in [Some(option1), Some(option2), option3].into_iter().flatten() {
+        let time = parse_time(&option)
+            .ok_or_else(|| BotError::UserError(format!("'{}' is not a valid HH:MM time.", option)))?;
+        if options.contains(&time) {
+            return Err(BotError::UserError(format!("{} is listed more than once.", option)));
+        }
+        options.push(time);
+    }
+
+    let duration_hours = duration_hours.unwrap_or(DEFAULT_VOTE_DURATION_HOURS);
+    if duration_hours == 0 || duration_hours > MAX_VOTE_DURATION_HOURS {
+        return Err(BotError::UserError(format!(
+            "The poll must run for between 1 and {} hours.",
+            MAX_VOTE_DURATION_HOURS
+        )));
+    }
+    let ends_at = Local::now() + chrono::Duration::hours(duration_hours as i64);
+
+    // Reserve the poll before posting so two admins can't start one at the same time. The
+    // message ID is filled in once the poll message has been sent.
+    {
+        let mut active_vote = ctx.data().active_vote.lock().await;
+        if active_vote.is_some() {
+            return Err(BotError::UserError("A summary time poll is already running.".to_string()));
+        }
+        *active_vote = Some(VotePoll {
+            channel_id: ctx.channel_id(),
+            message_id: serenity::MessageId(0),
+            options: options.clone(),
+            votes: HashMap::new(),
+            ends_at,
+            auto_apply: auto_apply.unwrap_or(true),
+            started_by: ctx.author().name.clone(),
+        });
+    }
+
+    let sent = ctx
+        .channel_id()
+        .send_message(ctx, |m| {
+            m.content(format!(
+                "🗳️ **When should the daily standup summary be posted?**\nVote below. The poll closes {}.",
+                ends_at.format("%A %H:%M")
+            ))
+            .components(|c| {
+                c.create_action_row(|row| {
+                    for (index, (hour, minute)) in options.iter().enumerate() {
+                        row.create_button(|b| {
+                            b.custom_id(format!("{}{}", VOTE_BUTTON_PREFIX, index))
+                                .label(format!("{:02}:{:02}", hour, minute))
+                                .style(serenity::ButtonStyle::Primary)
+                        });
+                    }
+                    row
+                })
+            })
+        })
+        .await;
+    let message = match sent {
+        Ok(message) => message,
+        Err(e) => {
+            *ctx.data().active_vote.lock().await = None;
+            return Err(e.into());
+        }
+    };
+    log_bot_message(ctx.data(), &message, MessageType::Poll, 0).await;
+
+    if let Some(poll) = ctx.data().active_vote.lock().await.as_mut() {
+        poll.message_id = message.id;
+    }
+    println!("Summary time poll started by {} ({})", ctx.author().name, ctx.author().id);
+
+    // Save the updated data
+    if let Err(e) = save_data(ctx.data()).await {
+        eprintln!("Failed to save data after starting poll: {}", e);
+    }
+
+    ctx.say("Poll started.").await?;
+
+    Ok(())
+}
+
+#[poise::command(slash_command, category = "Configuration", ephemeral)]
+/// Turn automatic summary channel topic updates on or off (admin only)
+async fn toggle_auto_topic(
+    ctx: Context<'_>,
+) -> Result<(), Error> {
+    // Check if the user has permission to manage channels
+    require_manage_channels(ctx).await?;
+
+    let enabled = {
+        let mut enabled = ctx.data().auto_update_topic.lock().await;
+        *enabled = !*enabled;
+        *enabled
+    };
+
+    // Save the updated data
+    if let Err(e) = save_data(ctx.data()).await {
+        eprintln!("Failed to save data after toggling topic updates: {}", e);
+        ctx.say("Topic updates toggled, but there was an error saving the configuration.").await?;
+        return Ok(());
+    }
+
+    if enabled {
+        ctx.say("The summary channel topic will be updated after each summary. I need the 'Manage Channels' permission there.").await?;
+    } else {
+        ctx.say("Summary channel topic updates disabled.").await?;
+    }
+
+    Ok(())
+}
+
+#[poise::command(slash_command, category = "Info", ephemeral)]
+/// Show a quick-start guide for using AgileMate in this server
+async fn onboarding(
+    ctx: Context<'_>,
+) -> Result<(), Error> {
+    let data = ctx.data();
+    let user_id = ctx.author().id.to_string();
+
+    let summary_channel = match *data.summary_channel_id.lock().await {
+        Some(id) => format!("<#{}>", id),
+        None => "not set up yet — ask an admin to run /set_summary_channel".to_string(),
+    };
+    let summary_time = *data.summary_time.lock().await;
+    let schedule = data.summary_schedule.lock().await.describe(summary_time);
+
+    let pending_entry = data
+        .standup_entries
+        .lock()
+        .await
+        .iter()
+        .filter(|e| e.user_id == user_id)
+        .max()
+        .cloned();
+    let your_status = match pending_entry {
+        Some(entry) => format!(
+            "You submitted at {}. Run /standup again to replace it before the summary.",
+            entry.timestamp.format("%Y-%m-%d %H:%M")
+        ),
+        None => "You haven't submitted a standup for the next summary yet.".to_string(),
+    };
+    let saved_plan = data.tomorrow_plans.contains_key(&user_id);
+
+    let mut tips = vec![
+        "Use /plan_for_tomorrow to save your plan ahead of time.",
+        "Use /skip_today if you're not doing standup today.",
+    ];
+    if saved_plan {
+        tips.push("You have a saved plan; leave the plan empty in /standup to use it.");
+    }
+
+    ctx.send(|m| {
+        m.embed(|e| {
+            e.title("Getting Started with AgileMate")
+                .description("Post your standup with /standup. Your answers are collected and shared in one summary.")
+                .field(
+                    "What to fill in",
+                    "**Did:** what you worked on\n**Plan:** what you'll work on next\n**Blockers:** anything in your way (\"none\" is fine)",
+                    false,
+                )
+                .field("Where summaries go", summary_channel, false)
+                .field("When summaries are sent", format!("{} (server time)", schedule), false)
+                .field("Reminders", "AgileMate doesn't send reminders, so remember to submit before the summary.", false)
+                .field("Your status", your_status, false)
+                .field("Tips", tips.join("\n"), false)
+        })
+    })
+    .await?;
+
+    Ok(())
+}
+
+#[poise::command(slash_command, category = "Standup", subcommands("my_config_set"))]
+/// Manage your personal AgileMate settings
+async fn my_config(
+    _ctx: Context<'_>,
+) -> Result<(), Error> {
+    Ok(())
+}
+
+#[poise::command(slash_command, ephemeral, rename = "set")]
+/// Change your personal AgileMate settings
+async fn my_config_set(
+    ctx: Context<'_>,
+    #[description = "Also send me a DM with a copy of each standup I submit"] dm_confirmation: bool,
+) -> Result<(), Error> {
+    ctx.data()
+        .user_prefs
+        .entry(ctx.author().id.to_string())
+        .or_default()
+        .dm_confirmation = dm_confirmation;
+
+    // Save the updated data
+    if let Err(e) = save_data(ctx.data()).await {
+        eprintln!("Failed to save data after updating user settings: {}", e);
+        ctx.say("Your settings have been updated, but there was an error saving the data.").await?;
+        return Ok(());
+    }
+
+    if dm_confirmation {
+        ctx.say("You'll get a DM with a copy of each standup you submit.").await?;
+    } else {
+        ctx.say("You won't get DM confirmations for your standups anymore.").await?;
+    }
+
+    Ok(())
+}
+
+#[poise::command(slash_command, category = "Info", ephemeral)]
+/// Show the current ISO week number and whether it is even or odd
+async fn current_week_parity(
+    ctx: Context<'_>,
+) -> Result<(), Error> {
+    let today = Local::now().date_naive();
+    let parity = BiWeeklyParity::of(today);
+
+    let schedule = *ctx.data().summary_schedule.lock().await;
+    let note = match schedule {
+        SummarySchedule::BiWeekly { week, .. } if week == parity => " Bi-weekly summaries are sent this week.",
+        SummarySchedule::BiWeekly { .. } => " Bi-weekly summaries are not sent this week.",
+        _ => "",
+    };
+
+    ctx.say(format!(
+        "This is ISO week {}, an {} week.{}",
+        today.iso_week().week(),
+        parity.name(),
+        note
+    ))
+    .await?;
+
+    Ok(())
+}
+
+// Order of the command groups in /standup_help; admin-only groups are only shown to admins
+const HELP_CATEGORIES: [(&str, bool); 4] =
+    [("Standup", false), ("Info", false), ("Configuration", true), ("Admin", true)];
+
+#[poise::command(slash_command, ephemeral, category = "Info")]
+/// Show help for AgileMate as it's set up in this server
+async fn standup_help(
+    ctx: Context<'_>,
+) -> Result<(), Error> {
+    let data = ctx.data();
+    let is_admin = require_manage_channels(ctx).await.is_ok();
+
+    let summary_channel = match *data.summary_channel_id.lock().await {
+        Some(id) => format!("<#{}>", id),
+        None => "not set yet".to_string(),
+    };
+    let summary_time = *data.summary_time.lock().await;
+    let schedule = data.summary_schedule.lock().await.describe(summary_time);
+
+    let quick_start = format!(
+        "1. Run /standup and fill in what you did, your plan and any blockers.\n\
+         2. Your answers are posted in {} — {} (server time).\n\
+         3. Run /standup again to replace your answers before the summary is sent.",
+        summary_channel, schedule
+    );
+    let fields = "**Did:** e.g. \"Finished the login page review\"\n\
+                  **Plan:** e.g. \"Start on the password reset flow\"\n\
+                  **Blockers:** e.g. \"Waiting on API keys\", or \"none\"";
+
+    let commands = &ctx.framework().options().commands;
+    let mut sections = Vec::new();
+    for (category, admin_only) in HELP_CATEGORIES {
+        if admin_only && !is_admin {
+            continue;
+        }
+
+        let in_category = commands
+            .iter()
+            .filter(|c| c.category == Some(category) && !c.hide_in_help);
+        let listing = if admin_only {
+            // Admin groups are long, so only list the names to stay within the embed field limit
+            in_category.map(|c| format!("/{}", c.name)).collect::<Vec<_>>().join(", ")
+        } else {
+            in_category
+                .map(|c| format!("/{} — {}", c.name, c.description.as_deref().unwrap_or("")))
+                .collect::<Vec<_>>()
+                .join("\n")
+        };
+        if !listing.is_empty() {
+            sections.push((format!("{} commands", category), listing));
+        }
+    }
+
+    ctx.send(|m| {
+        m.embed(|e| {
+            e.title("AgileMate Help")
+                .field("Quick start", quick_start, false)
+                .field("Standup fields", fields, false);
+            for (name, listing) in sections {
+                e.field(name, listing, false);
+            }
+            e
+        })
+    })
+    .await?;
+
+    Ok(())
+}
+
+#[poise::command(slash_command, category = "Admin", ephemeral)]
+/// List the links a user attached to their pending standup (admin only)
+async fn view_attachments(
+    ctx: Context<'_>,
+    #[description = "The user whose links to show"] user: serenity::User,
+    #[description = "The day the standup was submitted (YYYY-MM-DD)"] date: String,
+) -> Result<(), Error> {
+    // Check if the user has permission to manage channels
+    require_manage_channels(ctx).await?;
+
+    let date = NaiveDate::parse_from_str(&date, "%Y-%m-%d")
+        .map_err(|_| BotError::UserError(format!("'{}' is not a valid date. Use YYYY-MM-DD.", date)))?;
+
+    // Only pending entries are kept, so older standups can't be looked up
+    let attachments = ctx
+        .data()
+        .standup_entries
+        .lock()
+        .await
+        .iter()
+        .find(|e| e.user_id == user.id.to_string() && e.timestamp.date_naive() == date)
+        .map(|e| e.attachments.clone());
+
+    match attachments {
+        None => {
+            ctx.say(format!("{} has no pending standup from {}.", user.name, date)).await?;
+        }
+        Some(attachments) if attachments.is_empty() => {
+            ctx.say(format!("{}'s standup from {} has no links.", user.name, date)).await?;
+        }
+        Some(attachments) => {
+            let list = attachments.iter().map(|url| format!("- <{}>", url)).collect::<Vec<_>>().join("\n");
+            ctx.say(format!("Links in {}'s standup from {}:\n{}", user.name, date, list)).await?;
+        }
+    }
+
+    Ok(())
+}
+
+// Split pending entries into those from the current summary period and older ones
+fn categorize_queue(entries: &[StandupEntry], period_start: NaiveDate) -> (Vec<&StandupEntry>, Vec<&StandupEntry>) {
+    entries.iter().partition(|e| e.timestamp.date_naive() >= period_start)
+}
+
+// Shorten an answer for a one-line preview
+fn preview(text: &str, max_chars: usize) -> String {
+    if text.chars().count() <= max_chars {
+        text.to_string()
+    } else {
+        format!("{}…", text.chars().take(max_chars).collect::<String>())
+    }
+}
+
+// Discord rejects messages longer than this many characters
+const DISCORD_MESSAGE_LIMIT: usize = 2000;
+
+// Room kept free at the end of a list reply for a closing line such as the total
+const LIST_FOOTER_RESERVE: usize = 100;
+
+// Append as many `lines` to `report` as fit in `budget` characters, ending with
+// "…and N more" if some had to be left out
+fn push_lines_within(report: &mut String, lines: &[String], budget: usize) {
+    let more_line = |remaining: usize| format!("…and {} more\n", remaining);
+    let mut length = report.chars().count();
+
+    for (shown, line) in lines.iter().enumerate() {
+        let line_length = line.chars().count();
+        let is_last = shown + 1 == lines.len();
+        // Unless this is the last line, keep room for the "…and N more" line after it
+        let needed = if is_last { line_length } else { line_length + more_line(lines.len()).chars().count() };
+        if length + needed > budget {
+            report.push_str(&more_line(lines.len() - shown));
+            return;
+        }
+        report.push_str(line);
+        length += line_length;
+    }
+}
+
+fn queue_line(entry: &StandupEntry) -> String {
+    format!(
+        "- **{}** ({}): did: {} | plan: {} | blockers: {}\n",
+        entry.display_name,
+        entry.timestamp.format("%a %Y-%m-%d %H:%M"),
+        preview(&entry.did, 50),
+        preview(&entry.plan, 50),
+        preview(&entry.blockers, 50)
+    )
+}
+
+#[poise::command(slash_command, category = "Admin", ephemeral)]
+/// List the standups waiting for the next summary (admin only)
+async fn show_queue(
+    ctx: Context<'_>,
+    #[description = "Also list entries from before the current summary period"] include_old: Option<bool>,
+) -> Result<(), Error> {
+    // Check if the user has permission to manage channels
+    require_manage_channels(ctx).await?;
+
+    let mut entries = ctx.data().standup_entries.lock().await.clone();
+    if entries.is_empty() {
+        ctx.say("There are no pending standups.").await?;
+        return Ok(());
+    }
+    entries.sort_unstable_by(|a, b| b.cmp(a));
+
+    let period_start = ctx.data().summary_schedule.lock().await.period_start(Local::now().date_naive());
+    let (fresh, stale) = categorize_queue(&entries, period_start);
+
+    let show_old = include_old.unwrap_or(false) && !stale.is_empty();
+    let stale_header = format!(
+        "\n⚠️ **{} entries were submitted before the current summary period (since {}):**\n",
+        stale.len(),
+        period_start
+    );
+
+    // Keep room after the current entries for the older section's heading, or the "hidden" note
+    let mut report = format!("# Pending standups ({})\n\n", entries.len());
+    let fresh_budget = if show_old {
+        DISCORD_MESSAGE_LIMIT - LIST_FOOTER_RESERVE - stale_header.chars().count()
+    } else {
+        DISCORD_MESSAGE_LIMIT - LIST_FOOTER_RESERVE
+    };
+    let fresh_lines: Vec<String> = fresh.iter().map(|entry| queue_line(entry)).collect();
+    push_lines_within(&mut report, &fresh_lines, fresh_budget);
+
+    if show_old {
+        report.push_str(&stale_header);
+        let stale_lines: Vec<String> = stale.iter().map(|entry| queue_line(entry)).collect();
+        push_lines_within(&mut report, &stale_lines, DISCORD_MESSAGE_LIMIT);
+    } else if !stale.is_empty() {
+        report.push_str(&format!(
+            "\n{} older entries hidden. Use include_old:true to list them.",
+            stale.len()
+        ));
+    }
+
+    ctx.say(report).await?;
+
+    Ok(())
+}
+
+#[poise::command(slash_command, category = "Admin", ephemeral)]
+/// Write the bot's data in another storage format, keeping the current file (admin only)
+async fn convert_storage(
+    ctx: Context<'_>,
+    #[description = "The format to write"] format: StorageFormat,
+    #[description = "Only report the size of each format without writing anything"] dry_run: Option<bool>,
+) -> Result<(), Error> {
+    // Check if the user has permission to manage channels
+    require_manage_channels(ctx).await?;
+
+    let saved_data = saved_data_snapshot(ctx.data()).await;
+    let json_size = encode_saved_data(&saved_data, StorageFormat::Json)?.len();
+    let encoded = encode_saved_data(&saved_data, format)?;
+    let size_change = 100.0 * (encoded.len() as f64 - json_size as f64) / json_size.max(1) as f64;
+
+    let sizes = format!(
+        "JSON: {:.1} KB, {}: {:.1} KB ({:+.0}%)",
+        json_size as f64 / 1024.0,
+        format.name(),
+        encoded.len() as f64 / 1024.0,
+        size_change
+    );
+    if dry_run.unwrap_or(false) {
+        ctx.say(format!("Dry run, nothing was written. {}", sizes)).await?;
+        return Ok(());
+    }
+
+    let current = ctx.data().config.storage_format;
+    if format == current {
+        save_data(ctx.data()).await?;
+        ctx.say(format!("The bot already stores its data as {}; {} has been rewritten.", format.name(), format.path())).await?;
+        return Ok(());
+    }
+
+    fs::write(format.path(), encoded)?;
+    ctx.say(format!(
+        "Wrote {} ({}). Both files will be kept up to date until you run /confirm_storage_migration. \
+         To switch, set STORAGE_FORMAT={} and restart the bot, then run /confirm_storage_migration to delete {}.",
+        format.path(),
+        sizes,
+        format.name(),
+        current.path()
+    ))
+    .await?;
+
+    Ok(())
+}
+
+#[poise::command(slash_command, category = "Admin", ephemeral)]
+/// Delete the data file of the storage format that's no longer used (admin only)
+async fn confirm_storage_migration(
+    ctx: Context<'_>,
+) -> Result<(), Error> {
+    // Check if the user has permission to manage channels
+    require_manage_channels(ctx).await?;
+
+    let current = ctx.data().config.storage_format;
+    let old = current.other();
+
+    if !std::path::Path::new(old.path()).exists() {
+        ctx.say(format!("There's no {} to remove.", old.path())).await?;
+        return Ok(());
+    }
+
+    // Make sure the data is safely in the current format before deleting the old copy
+    save_data(ctx.data()).await?;
+    fs::remove_file(old.path())?;
+
+    ctx.say(format!("Deleted {}. Data is now only stored in {}.", old.path(), current.path())).await?;
+
+    Ok(())
+}
+
+#[poise::command(slash_command, category = "Configuration", ephemeral)]
+/// Set how many hours a pending standup is kept before it's dropped, 0 to keep it (admin only)
+async fn set_entry_expiry(
+    ctx: Context<'_>,
+    #[description = "Hours to keep pending standups (default 48, 0 to never drop them)"] hours: u32,
+) -> Result<(), Error> {
+    // Check if the user has permission to manage channels
+    require_manage_channels(ctx).await?;
+
+    *ctx.data().pending_entry_expiry_hours.lock().await = hours;
+
+    // Save the updated data
+    if let Err(e) = save_data(ctx.data()).await {
+        eprintln!("Failed to save data after setting entry expiry: {}", e);
+        ctx.say("Entry expiry set, but there was an error saving the configuration.").await?;
+        return Ok(());
+    }
+
+    if hours == 0 {
+        ctx.say("Pending standups will be kept until the next summary, however old they are.").await?;
+    } else {
+        ctx.say(format!(
+            "Pending standups older than {} hours from before the current summary period will be dropped instead of summarized.",
+            hours
+        ))
+        .await?;
+    }
+
+    Ok(())
+}
+
+// Furthest back /channel_report can look
+const CHANNEL_REPORT_MAX_DAYS: u32 = 365;
+
+#[poise::command(slash_command, category = "Admin", ephemeral)]
+/// List the messages the bot has posted in a channel recently (admin only)
+async fn channel_report(
+    ctx: Context<'_>,
+    #[description = "The channel to report on"] channel: serenity::ChannelId,
+    #[description = "How many days back to look, up to 365 (default 7)"] days: Option<u32>,
+) -> Result<(), Error> {
+    // Check if the user has permission to manage channels
+    require_manage_channels(ctx).await?;
+
+    let days = days.unwrap_or(7);
+    if days == 0 || days > CHANNEL_REPORT_MAX_DAYS {
+        return Err(BotError::UserError(format!(
+            "The report can look back between 1 and {} days.",
+            CHANNEL_REPORT_MAX_DAYS
+        )));
+    }
+    let since = Local::now() - chrono::Duration::days(days as i64);
+
+    let messages: Vec<MessageLogEntry> = ctx
+        .data()
+        .message_log
+        .lock()
+        .await
+        .iter()
+        .filter(|m| m.channel_id == channel && m.timestamp >= since)
+        .cloned()
+        .collect();
+
+    if messages.is_empty() {
+        ctx.say(format!("I haven't posted in <#{}> in the last {} days.", channel, days)).await?;
+        return Ok(());
+    }
+
+    let mut report = format!("# Bot messages in <#{}> (last {} days)\n\n", channel, days);
+    let lines: Vec<String> = messages
+        .iter()
+        .rev()
+        .map(|message| {
+            let entries = if message.entry_count > 0 {
+                format!(" ({} entries)", message.entry_count)
+            } else {
+                String::new()
+            };
+            format!(
+                "- {} {:?}{} — {}\n",
+                message.timestamp.format("%a %Y-%m-%d %H:%M"),
+                message.message_type,
+                entries,
+                message.message_id.link(channel, ctx.guild_id())
+            )
+        })
+        .collect();
+    // Keep the reply within Discord's message length limit, leaving room for the total
+    push_lines_within(&mut report, &lines, DISCORD_MESSAGE_LIMIT - LIST_FOOTER_RESERVE);
+    report.push_str(&format!("\n**Total:** {}", messages.len()));
+
+    ctx.say(report).await?;
+
+    Ok(())
+}
+
+// Image types Discord can show in an embed
+const SUMMARY_IMAGE_EXTENSIONS: [&str; 5] = ["png", "jpg", "jpeg", "gif", "webp"];
+
+// Check that a summary image URL is https and ends in an image extension, ignoring any query string
+fn validate_summary_image_url(url: &str) -> Result<(), String> {
+    if !url.starts_with("https://") {
+        return Err("The image URL must start with https://.".to_string());
     }
-    
-    // Save the updated data
-    if let Err(e) = save_data(ctx.data()).await {
-        eprintln!("Failed to save data after standup submission: {}", e);
-        ctx.say("Your standup has been recorded, but there was an error saving the data.").await?;
-    } else {
-        ctx.say("Your standup has been recorded. Thanks!").await?;
+
+    // Only the last path segment counts, so a bare host such as https://logo.png isn't taken as an image
+    let without_query = url.split(['?', '#']).next().unwrap_or(url);
+    let file_name = without_query["https://".len()..]
+        .split_once('/')
+        .and_then(|(_, path)| path.rsplit('/').next())
+        .unwrap_or("");
+    let extension = file_name.rsplit_once('.').map(|(_, ext)| ext.to_lowercase());
+    if !extension.is_some_and(|ext| SUMMARY_IMAGE_EXTENSIONS.contains(&ext.as_str())) {
+        return Err(format!(
+            "The image URL must end in one of: {}.",
+            SUMMARY_IMAGE_EXTENSIONS.map(|ext| format!(".{}", ext)).join(", ")
+        ));
     }
-    
+
     Ok(())
 }
 
-#[poise::command(slash_command, ephemeral)]
-/// Set the channel for daily summaries
-async fn set_summary_channel(
+#[poise::command(slash_command, category = "Configuration", ephemeral)]
+/// Show an image, such as a team logo, below every summary; supports {date} (admin only)
+async fn set_summary_image(
     ctx: Context<'_>,
-    #[description = "The channel for daily summaries"] channel_id: serenity::ChannelId,
+    #[description = "https:// URL of a .png, .jpg, .gif or .webp image"] url: String,
 ) -> Result<(), Error> {
-    // Check if the command is being used in a server
-    let guild_id = match ctx.guild_id() {
-        Some(id) => id,
-        None => {
-            ctx.say("This command can only be used in a server.").await?;
-            return Ok(());
-        }
-    };
+    // Check if the user has permission to manage channels
+    require_manage_channels(ctx).await?;
 
-    // Fetch the guild to get the owner ID
-    let guild = match ctx.http().get_guild(guild_id.0).await {
-        Ok(guild) => guild,
-        Err(e) => {
-            println!("Failed to fetch guild information: {:?}", e);
-            ctx.say("Failed to fetch guild information. Please try again later.").await?;
-            return Ok(());
-        }
-    };
+    let url = url.trim().to_string();
+    validate_summary_image_url(&url).map_err(BotError::UserError)?;
 
-    // Check if the user is the server owner or has the "Manage Channels" permission
-    let is_owner = ctx.author().id == guild.owner_id;
-    let has_permission = ctx.author_member().await
-        .map_or(false, |member| member.permissions(ctx).map_or(false, |p| p.manage_channels()));
+    *ctx.data().summary_image_url.lock().await = Some(url.clone());
 
-    if !is_owner && !has_permission {
-        ctx.say("You need 'Manage Channels' permission to use this command.").await?;
+    // Save the updated data
+    if let Err(e) = save_data(ctx.data()).await {
+        eprintln!("Failed to save data after setting summary image: {}", e);
+        ctx.say("Summary image set, but there was an error saving the configuration.").await?;
         return Ok(());
     }
 
-    // Verify that the channel exists and is accessible
-    match channel_id.to_channel(&ctx).await {
-        Ok(_) => {
-            // Set the summary channel ID in the shared data
-            *ctx.data().summary_channel_id.lock().await = Some(channel_id);
+    ctx.say(format!("Summary image set to <{}>. If Discord can't load it, summaries are posted without it.", url))
+        .await?;
 
-            // Save the updated data
-            if let Err(e) = save_data(ctx.data()).await {
-                eprintln!("Failed to save data after setting summary channel: {}", e);
-                ctx.say("Summary channel set, but there was an error saving the configuration.").await?;
-                return Ok(());
-            }
+    Ok(())
+}
 
-            // Notify the user
-            ctx.say(format!("Summary channel set to <#{}>", channel_id)).await?;
-        }
-        Err(_) => {
-            ctx.say("Invalid channel or I don't have access to it.").await?;
-        }
+#[poise::command(slash_command, category = "Configuration", ephemeral)]
+/// Stop showing an image below summaries (admin only)
+async fn clear_summary_image(
+    ctx: Context<'_>,
+) -> Result<(), Error> {
+    // Check if the user has permission to manage channels
+    require_manage_channels(ctx).await?;
+
+    *ctx.data().summary_image_url.lock().await = None;
+
+    // Save the updated data
+    if let Err(e) = save_data(ctx.data()).await {
+        eprintln!("Failed to save data after clearing summary image: {}", e);
+        ctx.say("Summary image cleared, but there was an error saving the configuration.").await?;
+        return Ok(());
     }
 
+    ctx.say("Summary image cleared.").await?;
+
     Ok(())
 }
 
-#[poise::command(slash_command, ephemeral)]
-/// Set the time when daily summaries will be sent (24-hour format)
-async fn set_summary_time(
+#[poise::command(slash_command, category = "Configuration", ephemeral)]
+/// Set the minimum hours between repeated warnings of the same kind (admin only)
+async fn set_notification_cooldown(
     ctx: Context<'_>,
-    #[description = "Hour (0-23)"] hour: u32,
-    #[description = "Minute (0-59)"] minute: u32,
+    #[description = "Hours between warnings of the same kind (default 1, 0 for no limit)"] hours: u32,
 ) -> Result<(), Error> {
     // Check if the user has permission to manage channels
-    if let Some(member) = ctx.author_member().await {
-        if !member.permissions(ctx).map_or(false, |p| p.manage_channels()) {
-            ctx.say("You need 'Manage Channels' permission to use this command.").await?;
-            return Ok(());
-        }
-    }
-    
-    // Validate hour and minute
-    if hour > 23 || minute > 59 {
-        ctx.say("Invalid time. Hour must be between 0-23 and minute between 0-59.").await?;
+    require_manage_channels(ctx).await?;
+
+    *ctx.data().notification_cooldown_hours.lock().await = hours;
+
+    // Save the updated data
+    if let Err(e) = save_data(ctx.data()).await {
+        eprintln!("Failed to save data after setting notification cooldown: {}", e);
+        ctx.say("Notification cooldown set, but there was an error saving the configuration.").await?;
         return Ok(());
     }
-    
-    // Set the summary time
-    *ctx.data().summary_time.lock().await = (hour, minute);
-    
+
+    if hours == 0 {
+        ctx.say("Every warning will be posted, however often it happens.").await?;
+    } else {
+        ctx.say(format!("The same kind of warning will be posted at most once every {} hours.", hours)).await?;
+    }
+
+    Ok(())
+}
+
+#[poise::command(slash_command, category = "Configuration", ephemeral)]
+/// Set a channel that shows a running tally of submitted standups (admin only)
+async fn set_digest_channel(
+    ctx: Context<'_>,
+    #[description = "The channel for the standup tally"] channel_id: serenity::ChannelId,
+) -> Result<(), Error> {
+    // Check if the user has permission to manage channels
+    require_manage_channels(ctx).await?;
+
+    // Verify that the channel exists and is accessible
+    if channel_id.to_channel(&ctx).await.is_err() {
+        return Err(BotError::UserError("Invalid channel or I don't have access to it.".to_string()));
+    }
+
+    *ctx.data().digest_channel_id.lock().await = Some(channel_id);
+    // Start a new tally message in the new channel
+    *ctx.data().digest_message_id.lock().await = None;
+
     // Save the updated data
     if let Err(e) = save_data(ctx.data()).await {
-        eprintln!("Failed to save data after setting summary time: {}", e);
-        ctx.say("Summary time set, but there was an error saving the configuration.").await?;
+        eprintln!("Failed to save data after setting digest channel: {}", e);
+        ctx.say("Digest channel set, but there was an error saving the configuration.").await?;
         return Ok(());
     }
-    
-    ctx.say(format!("Summary time set to {:02}:{:02}", hour, minute)).await?;
-    
+
+    update_digest(ctx.serenity_context(), ctx.data(), digest_tally(ctx.data()).await).await;
+
+    ctx.say(format!("Digest channel set to <#{}>", channel_id)).await?;
+
     Ok(())
 }
 
-#[poise::command(slash_command, ephemeral)]
-/// Manually trigger a standup summary (admin only)
-async fn trigger_summary(
+#[poise::command(slash_command, category = "Configuration", ephemeral)]
+/// Stop updating the standup tally in the digest channel (admin only)
+async fn clear_digest_channel(
     ctx: Context<'_>,
 ) -> Result<(), Error> {
     // Check if the user has permission to manage channels
-    if let Some(member) = ctx.author_member().await {
-        if !member.permissions(ctx).map_or(false, |p| p.manage_channels()) {
-            ctx.say("You need 'Manage Channels' permission to use this command.").await?;
-            return Ok(());
-        }
-    } else {
-        ctx.say("This command can only be used in a server.").await?;
+    require_manage_channels(ctx).await?;
+
+    *ctx.data().digest_channel_id.lock().await = None;
+    *ctx.data().digest_message_id.lock().await = None;
+
+    // Save the updated data
+    if let Err(e) = save_data(ctx.data()).await {
+        eprintln!("Failed to save data after clearing digest channel: {}", e);
+        ctx.say("Digest channel cleared, but there was an error saving the configuration.").await?;
         return Ok(());
     }
-    
-    ctx.say("Manually triggering standup summary...").await?;
-    
-    // Send the summary
-    match send_summary(&ctx.serenity_context().clone(), ctx.data()).await {
-        Ok(_) => {
-            // Update the last summary date
-            *ctx.data().last_summary_date.lock().await = Some(Local::now().date_naive());
-            if let Err(e) = save_data(ctx.data()).await {
-                eprintln!("Failed to save data after manual summary: {}", e);
-            }
-            
-            ctx.say("Summary sent successfully!").await?;
-        },
-        Err(e) => {
-            ctx.say(format!("Failed to send summary: {}", e)).await?;
+
+    ctx.say("Digest channel cleared.").await?;
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    fn at(day: u32, hour: u32, minute: u32) -> DateTime<Local> {
+        Local.with_ymd_and_hms(2024, 1, day, hour, minute, 0).unwrap()
+    }
+
+    fn entry(user_id: &str, display_name: &str, timestamp: DateTime<Local>) -> StandupEntry {
+        StandupEntry {
+            user_id: user_id.to_string(),
+            display_name: display_name.to_string(),
+            did: "Reviewed PRs".to_string(),
+            plan: "Write docs".to_string(),
+            blockers: "none".to_string(),
+            timestamp,
+            attachments: Vec::new(),
+            blocker_severity: None,
         }
     }
-    
-    Ok(())
-}
\ No newline at end of file
+
+    #[test]
+    fn entries_with_equal_timestamps_are_ordered_by_user_id() {
+        let first = entry("100", "Zed", at(15, 9, 0));
+        let second = entry("200", "Amy", at(15, 9, 0));
+
+        assert!(first < second);
+        assert_ne!(first, second);
+        assert_eq!(first.cmp(&first.clone()), std::cmp::Ordering::Equal);
+    }
+
+    #[test]
+    fn shuffled_entries_sort_by_timestamp_then_user_id() {
+        let expected = vec![
+            entry("1", "Ann", at(15, 8, 0)),
+            entry("2", "Bob", at(15, 8, 0)),
+            entry("1", "Ann", at(15, 9, 30)),
+            entry("3", "Cid", at(15, 10, 0)),
+            entry("2", "Bob", at(16, 7, 45)),
+        ];
+
+        let mut entries = expected.clone();
+        entries.shuffle(&mut rand::thread_rng());
+        entries.sort();
+
+        assert_eq!(entries, expected);
+    }
+
+    #[test]
+    fn push_lines_within_stops_before_the_budget() {
+        let lines: Vec<String> = (0..50).map(|i| format!("- line {:02} {}\n", i, "x".repeat(80))).collect();
+        let mut report = "# Header\n".to_string();
+        push_lines_within(&mut report, &lines, 1000);
+
+        assert!(report.chars().count() <= 1000);
+        assert!(report.ends_with("more\n"));
+        let shown = report.matches("- line").count();
+        assert!(report.ends_with(&format!("…and {} more\n", lines.len() - shown)));
+    }
+
+    #[test]
+    fn push_lines_within_keeps_everything_that_fits() {
+        let lines = vec!["- a\n".to_string(), "- b\n".to_string()];
+        let mut report = String::new();
+        push_lines_within(&mut report, &lines, 100);
+
+        assert_eq!(report, "- a\n- b\n");
+    }
+
+    fn sort_fixture() -> Vec<StandupEntry> {
+        vec![
+            entry("3", "carol", at(15, 9, 0)),
+            entry("1", "Bob", at(15, 8, 0)),
+            entry("2", "alice", at(15, 10, 0)),
+        ]
+    }
+
+    fn names(entries: &[StandupEntry]) -> Vec<&str> {
+        entries.iter().map(|e| e.display_name.as_str()).collect()
+    }
+
+    #[test]
+    fn sort_entries_alphabetical_ignores_case() {
+        let mut entries = sort_fixture();
+        sort_entries(&mut entries, SortOrder::Alphabetical);
+        assert_eq!(names(&entries), ["alice", "Bob", "carol"]);
+    }
+
+    #[test]
+    fn sort_entries_by_submission_time() {
+        let mut entries = sort_fixture();
+        sort_entries(&mut entries, SortOrder::SubmissionTime);
+        assert_eq!(names(&entries), ["Bob", "carol", "alice"]);
+    }
+
+    #[test]
+    fn sort_entries_random_keeps_every_entry() {
+        let mut entries = sort_fixture();
+        sort_entries(&mut entries, SortOrder::Random);
+        entries.sort();
+
+        let mut expected = sort_fixture();
+        expected.sort();
+        assert_eq!(entries, expected);
+    }
+
+    #[test]
+    fn saved_team_grouped_order_loads_as_alphabetical() {
+        let order: SortOrder = serde_json::from_str("\"TeamGrouped\"").unwrap();
+        assert_eq!(order, SortOrder::Alphabetical);
+    }
+
+    #[test]
+    fn default_phrases_are_trivial_blockers() {
+        let phrases = default_trivial_blocker_phrases();
+        for phrase in DEFAULT_TRIVIAL_BLOCKER_PHRASES {
+            assert!(is_trivial_blocker(phrase, &phrases), "{:?} should be trivial", phrase);
+        }
+        assert!(is_trivial_blocker("", &phrases));
+        assert!(is_trivial_blocker("   ", &phrases));
+    }
+
+    #[test]
+    fn trivial_blocker_check_ignores_case_and_surrounding_whitespace() {
+        let phrases = default_trivial_blocker_phrases();
+        assert!(is_trivial_blocker("  None ", &phrases));
+        assert!(is_trivial_blocker("N/A", &phrases));
+        assert!(is_trivial_blocker("All Good", &phrases));
+    }
+
+    #[test]
+    fn real_blockers_are_not_trivial() {
+        let phrases = default_trivial_blocker_phrases();
+        assert!(!is_trivial_blocker("Waiting on API keys", &phrases));
+        assert!(!is_trivial_blocker("none of the tests pass", &phrases));
+    }
+
+    #[test]
+    fn custom_trivial_phrases_are_matched() {
+        let mut phrases = default_trivial_blocker_phrases();
+        assert!(!is_trivial_blocker("smooth sailing", &phrases));
+
+        phrases.insert(normalize_phrase("Smooth Sailing"));
+        assert!(is_trivial_blocker(" smooth SAILING", &phrases));
+
+        phrases.remove("none");
+        assert!(!is_trivial_blocker("none", &phrases));
+    }
+
+    #[test]
+    fn should_fire_at_respects_the_summary_window() {
+        let schedule = SummarySchedule::Daily;
+        let time = (17, 0);
+
+        assert!(!should_fire_at(schedule, time, at(15, 16, 59)), "before the target");
+        assert!(should_fire_at(schedule, time, at(15, 17, 0)), "at the exact target");
+        assert!(should_fire_at(schedule, time, at(15, 17, 4)), "inside the window");
+        assert!(!should_fire_at(schedule, time, at(15, 17, 5)), "after the window");
+        assert!(!should_fire_at(schedule, time, at(15, 18, 0)), "an hour later");
+    }
+
+    #[test]
+    fn should_fire_at_window_can_cross_the_hour() {
+        assert!(should_fire_at(SummarySchedule::Daily, (17, 58), at(15, 18, 1)));
+        assert!(!should_fire_at(SummarySchedule::Daily, (17, 58), at(15, 18, 3)));
+    }
+
+    #[test]
+    fn should_fire_at_only_on_summary_days() {
+        // 2024-01-15 is a Monday
+        let schedule = SummarySchedule::Weekly { day: Weekday::Mon };
+        assert!(should_fire_at(schedule, (9, 0), at(15, 9, 0)));
+        assert!(!should_fire_at(schedule, (9, 0), at(16, 9, 0)));
+    }
+
+    fn local(year: i32, month: u32, day: u32, hour: u32, minute: u32) -> DateTime<Local> {
+        Local.with_ymd_and_hms(year, month, day, hour, minute, 0).unwrap()
+    }
+
+    // In 2024, ISO week 2 starts on Monday 8 January and week 3 on Monday 15 January
+    const EVEN_MONDAYS: SummarySchedule = SummarySchedule::BiWeekly { day: Weekday::Mon, week: BiWeeklyParity::Even };
+
+    #[test]
+    fn biweekly_next_fire_in_the_right_week_is_the_same_day() {
+        let next = EVEN_MONDAYS.next_fire(local(2024, 1, 8, 9, 0), (14, 0));
+        assert_eq!(next, Some(local(2024, 1, 8, 14, 0)));
+    }
+
+    #[test]
+    fn biweekly_next_fire_skips_the_odd_week() {
+        // After the even-week summary has gone out, the next one is two weeks later
+        let next = EVEN_MONDAYS.next_fire(local(2024, 1, 8, 15, 0), (14, 0));
+        assert_eq!(next, Some(local(2024, 1, 22, 14, 0)));
+
+        // From inside the odd week
+        let next = EVEN_MONDAYS.next_fire(local(2024, 1, 17, 12, 0), (14, 0));
+        assert_eq!(next, Some(local(2024, 1, 22, 14, 0)));
+    }
+
+    #[test]
+    fn biweekly_next_fire_across_the_even_odd_boundary() {
+        // Sunday 14 January is the last day of even week 2; Monday 15 January starts odd week 3
+        let next = EVEN_MONDAYS.next_fire(local(2024, 1, 14, 20, 0), (14, 0));
+        assert_eq!(next, Some(local(2024, 1, 22, 14, 0)));
+
+        let odd_mondays = SummarySchedule::BiWeekly { day: Weekday::Mon, week: BiWeeklyParity::Odd };
+        let next = odd_mondays.next_fire(local(2024, 1, 14, 20, 0), (14, 0));
+        assert_eq!(next, Some(local(2024, 1, 15, 14, 0)));
+    }
+
+    #[test]
+    fn odd_biweekly_fires_two_weeks_running_after_a_53_week_year() {
+        // 2020 has 53 ISO weeks: Monday 28 December is week 53 and Monday 4 January 2021 is week 1
+        let odd_mondays = SummarySchedule::BiWeekly { day: Weekday::Mon, week: BiWeeklyParity::Odd };
+        assert!(odd_mondays.fires_on(NaiveDate::from_ymd_opt(2020, 12, 28).unwrap()));
+
+        let next = odd_mondays.next_fire(local(2020, 12, 28, 15, 0), (14, 0));
+        assert_eq!(next, Some(local(2021, 1, 4, 14, 0)));
+    }
+
+    #[test]
+    fn even_biweekly_waits_three_weeks_after_a_53_week_year() {
+        // 2026 has 53 ISO weeks: 21 December is week 52, 28 December is week 53 and
+        // 4 January 2027 is week 1, so the next even Monday is 11 January
+        assert!(EVEN_MONDAYS.fires_on(NaiveDate::from_ymd_opt(2026, 12, 21).unwrap()));
+
+        let next = EVEN_MONDAYS.next_fire(local(2026, 12, 21, 15, 0), (14, 0));
+        assert_eq!(next, Some(local(2027, 1, 11, 14, 0)));
+    }
+
+    #[test]
+    fn parse_biweekly_schedule() {
+        assert_eq!(
+            parse_schedule("biweekly monday 14:00 even"),
+            Ok((EVEN_MONDAYS, Some((14, 0))))
+        );
+        assert_eq!(
+            parse_schedule("BiWeekly fri odd"),
+            Ok((SummarySchedule::BiWeekly { day: Weekday::Fri, week: BiWeeklyParity::Odd }, None))
+        );
+        assert!(parse_schedule("biweekly monday 14:00").is_err());
+        assert!(parse_schedule("biweekly monday").is_err());
+    }
+
+    #[test]
+    fn saved_data_round_trips_through_msgpack() {
+        let json = serde_json::json!({
+            "standup_entries": [entry("1", "Ann", at(15, 9, 0))],
+            "summary_channel_id": "123456789012345678",
+            "summary_time": [9, 30],
+            "summary_schedule": EVEN_MONDAYS,
+            "tomorrow_plans": { "1": { "plan": "Ship it", "saved_on": "2024-01-15" } },
+        });
+        let saved: SavedData = serde_json::from_value(json).unwrap();
+
+        let packed = encode_saved_data(&saved, StorageFormat::MsgPack).unwrap();
+        let unpacked: SavedData = rmp_serde::from_slice(&packed).unwrap();
+
+        assert_eq!(
+            serde_json::to_value(&unpacked).unwrap(),
+            serde_json::to_value(&saved).unwrap()
+        );
+    }
+
+    #[test]
+    fn msgpack_is_smaller_than_json_for_a_large_payload() {
+        let entries: Vec<StandupEntry> = (0..10_000)
+            .map(|i| {
+                let mut entry = entry(&(100_000 + i).to_string(), &format!("user{}", i), at(1 + i % 28, 9, i % 60));
+                entry.attachments = vec![format!("https://github.com/org/repo/pull/{}", i)];
+                entry.blocker_severity = (i % 3 == 0).then_some(BlockerSeverity::High);
+                entry
+            })
+            .collect();
+        let saved: SavedData = serde_json::from_value(serde_json::json!({ "standup_entries": entries })).unwrap();
+
+        let started = std::time::Instant::now();
+        let json = encode_saved_data(&saved, StorageFormat::Json).unwrap();
+        let json_time = started.elapsed();
+        let started = std::time::Instant::now();
+        let packed = encode_saved_data(&saved, StorageFormat::MsgPack).unwrap();
+        let msgpack_time = started.elapsed();
+        println!(
+            "10,000 entries: JSON {} bytes in {:?}, MessagePack {} bytes in {:?}",
+            json.len(),
+            json_time,
+            packed.len(),
+            msgpack_time
+        );
+
+        assert!(packed.len() < json.len());
+        let unpacked: SavedData = rmp_serde::from_slice(&packed).unwrap();
+        assert_eq!(unpacked.standup_entries, saved.standup_entries);
+        assert_eq!(
+            serde_json::to_value(&unpacked).unwrap(),
+            serde_json::to_value(&saved).unwrap()
+        );
+    }
+
+    #[test]
+    fn detect_severity_matches_whole_words_only() {
+        let keywords = default_blocker_keywords();
+        assert_eq!(detect_severity("Slow download of the dataset", &keywords), Some(BlockerSeverity::Medium));
+        assert_eq!(detect_severity("download finished", &keywords), None);
+        assert_eq!(detect_severity("Server is DOWN", &keywords), Some(BlockerSeverity::Critical));
+    }
+
+    #[test]
+    fn detect_severity_picks_the_highest_match() {
+        let keywords = default_blocker_keywords();
+        assert_eq!(detect_severity("production outage, stuck", &keywords), Some(BlockerSeverity::Critical));
+        assert_eq!(detect_severity("stuck, waiting on review", &keywords), Some(BlockerSeverity::High));
+        assert_eq!(detect_severity("waiting on review", &keywords), Some(BlockerSeverity::Medium));
+        assert_eq!(detect_severity("", &keywords), None);
+        assert_eq!(detect_severity("None", &keywords), None);
+    }
+
+    #[test]
+    fn detect_severity_matches_phrases_anywhere() {
+        let mut keywords = default_blocker_keywords();
+        keywords.entry(BlockerSeverity::High).or_default().push("code freeze".to_string());
+        assert_eq!(detect_severity("Release is in Code Freeze until Friday", &keywords), Some(BlockerSeverity::High));
+        assert_eq!(detect_severity("freeze on the code", &keywords), None);
+    }
+
+    fn template_variables() -> HashMap<&'static str, String> {
+        HashMap::from([("display_name", "Zoë".to_string()), ("did", "Shipped".to_string())])
+    }
+
+    #[test]
+    fn render_template_substitutes_known_variables() {
+        assert_eq!(render_template("**{display_name}**: {did}", &template_variables()), "**Zoë**: Shipped");
+        assert_eq!(render_template("{did}{did}", &template_variables()), "ShippedShipped");
+        assert_eq!(render_template("", &template_variables()), "");
+    }
+
+    #[test]
+    fn render_template_leaves_unknown_variables_as_text() {
+        assert_eq!(render_template("{did} {mood}", &template_variables()), "Shipped {mood}");
+        assert_eq!(render_template("{} {did}", &template_variables()), "{} Shipped");
+    }
+
+    #[test]
+    fn render_template_handles_unbalanced_braces() {
+        assert_eq!(render_template("{did} and {display_name", &template_variables()), "Shipped and {display_name");
+        assert_eq!(render_template("} {did}", &template_variables()), "} Shipped");
+        assert_eq!(render_template("{a{did}", &template_variables()), "{aShipped");
+        assert_eq!(render_template("{a{b}", &template_variables()), "{a{b}");
+    }
+
+    #[test]
+    fn render_template_keeps_multi_byte_text_next_to_braces() {
+        assert_eq!(render_template("→{did}← ✅{display_name}🎉", &template_variables()), "→Shipped← ✅Zoë🎉");
+        assert_eq!(render_template("日本{語", &template_variables()), "日本{語");
+        assert_eq!(render_template("{ñ}", &template_variables()), "{ñ}");
+    }
+
+    #[test]
+    fn missing_entry_variables_lists_required_variables() {
+        assert!(missing_entry_variables("{display_name}: {did} / {plan}").is_empty());
+        assert_eq!(missing_entry_variables("{display_name}: {did}"), ["{plan}"]);
+        assert_eq!(missing_entry_variables("{display_name {did} plan}"), ["{display_name}", "{plan}"]);
+        assert_eq!(missing_entry_variables(""), REQUIRED_ENTRY_VARIABLES);
+    }
+
+    #[test]
+    fn parse_attachments_splits_on_commas_and_spaces() {
+        assert_eq!(
+            parse_attachments("https://a.com/x, https://b.org?y=1,https://c.io\thttps://d.dev/#z"),
+            Ok(vec![
+                "https://a.com/x".to_string(),
+                "https://b.org?y=1".to_string(),
+                "https://c.io".to_string(),
+                "https://d.dev/#z".to_string(),
+            ])
+        );
+        assert_eq!(parse_attachments(" , "), Ok(vec![]));
+    }
+
+    #[test]
+    fn parse_attachments_rejects_invalid_links() {
+        assert!(parse_attachments("http://example.com").is_err());
+        assert!(parse_attachments("https://example.com example.com").is_err());
+        assert!(parse_attachments("https://localhost/build").is_err());
+        assert!(parse_attachments("https://.com").is_err());
+    }
+
+    #[test]
+    fn parse_attachments_limits_the_number_of_links() {
+        let links = |count: usize| (0..count).map(|i| format!("https://site{}.com", i)).collect::<Vec<_>>().join(" ");
+        assert_eq!(parse_attachments(&links(MAX_ATTACHMENTS)).map(|l| l.len()), Ok(MAX_ATTACHMENTS));
+        assert!(parse_attachments(&links(MAX_ATTACHMENTS + 1)).is_err());
+    }
+
+    #[test]
+    fn format_attachments_links_by_domain() {
+        let links = vec!["https://github.com/org/repo/pull/1".to_string(), "https://docs.rs?q=x".to_string()];
+        assert_eq!(
+            format_attachments(&links),
+            "[github.com](<https://github.com/org/repo/pull/1>), [docs.rs](<https://docs.rs?q=x>)"
+        );
+    }
+
+    #[test]
+    fn period_start_covers_the_summary_period() {
+        let today = NaiveDate::from_ymd_opt(2024, 1, 15).unwrap();
+        assert_eq!(SummarySchedule::Daily.period_start(today), today);
+        assert_eq!(
+            SummarySchedule::Weekly { day: Weekday::Mon }.period_start(today),
+            NaiveDate::from_ymd_opt(2024, 1, 9).unwrap()
+        );
+        assert_eq!(EVEN_MONDAYS.period_start(today), NaiveDate::from_ymd_opt(2024, 1, 2).unwrap());
+    }
+
+    #[test]
+    fn daily_entries_expire_after_the_configured_hours() {
+        let now = at(15, 12, 0);
+        let period_start = SummarySchedule::Daily.period_start(now.date_naive());
+        let expired = |timestamp| is_entry_expired(&entry("1", "Ann", timestamp), now, 48, period_start);
+
+        assert!(expired(at(13, 11, 0)));
+        assert!(!expired(at(13, 13, 0)));
+        assert!(!expired(at(15, 0, 0)));
+    }
+
+    #[test]
+    fn entries_in_the_current_weekly_period_never_expire() {
+        let now = at(15, 12, 0);
+        let period_start = SummarySchedule::Weekly { day: Weekday::Mon }.period_start(now.date_naive());
+        let expired = |timestamp| is_entry_expired(&entry("1", "Ann", timestamp), now, 48, period_start);
+
+        // Tuesday 9 January starts the period ending Monday 15 January
+        assert!(!expired(at(9, 0, 0)));
+        assert!(!expired(at(10, 9, 0)));
+        assert!(expired(at(8, 23, 59)));
+    }
+
+    #[test]
+    fn zero_expiry_hours_keeps_every_entry() {
+        let now = at(15, 12, 0);
+        let old = entry("1", "Ann", at(1, 9, 0));
+        assert!(!is_entry_expired(&old, now, 0, now.date_naive()));
+        assert!(is_entry_expired(&old, now, 1, now.date_naive()));
+    }
+
+    #[test]
+    fn summary_image_url_accepts_image_extensions() {
+        assert_eq!(validate_summary_image_url("https://example.com/logo.png"), Ok(()));
+        assert_eq!(validate_summary_image_url("https://cdn.example.com/a/b/team.JPEG"), Ok(()));
+        assert_eq!(validate_summary_image_url("https://example.com/logo.WebP"), Ok(()));
+        assert_eq!(validate_summary_image_url("https://example.com/logo.gif?size=128&v=2"), Ok(()));
+        assert_eq!(validate_summary_image_url("https://example.com/logo.png#top"), Ok(()));
+    }
+
+    #[test]
+    fn summary_image_url_rejects_other_links() {
+        assert!(validate_summary_image_url("http://example.com/logo.png").is_err());
+        assert!(validate_summary_image_url("HTTPS://example.com/logo.png").is_err());
+        assert!(validate_summary_image_url("https://example.com/logo").is_err());
+        assert!(validate_summary_image_url("https://example.com/").is_err());
+        assert!(validate_summary_image_url("https://logo.png").is_err());
+        assert!(validate_summary_image_url("https://example.com/logo.svg").is_err());
+        assert!(validate_summary_image_url("https://example.com/page?image=logo.png").is_err());
+        assert!(validate_summary_image_url("https://example.com/images.png/view").is_err());
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 8)]
+    async fn per_user_maps_handle_concurrent_readers_and_writers() {
+        let tomorrow_plans: Arc<DashMap<String, TomorrowPlan>> = Arc::new(DashMap::new());
+        let user_prefs: Arc<DashMap<String, UserPrefs>> = Arc::new(DashMap::new());
+        let saved_on = NaiveDate::from_ymd_opt(2024, 1, 15).unwrap();
+
+        let mut tasks = Vec::new();
+        for writer in 0..10 {
+            let (tomorrow_plans, user_prefs) = (tomorrow_plans.clone(), user_prefs.clone());
+            tasks.push(tokio::spawn(async move {
+                for user in writer * 100..(writer + 1) * 100 {
+                    let user_id = user.to_string();
+                    // /plan_tomorrow, then /standup consuming every other plan, then /my_config
+                    tomorrow_plans.insert(user_id.clone(), TomorrowPlan { plan: format!("plan {}", user), saved_on });
+                    if user % 2 == 0 {
+                        tomorrow_plans.remove(&user_id);
+                    }
+                    user_prefs.entry(user_id).or_default().dm_confirmation = true;
+                    tokio::task::yield_now().await;
+                }
+            }));
+        }
+        for _ in 0..100 {
+            let (tomorrow_plans, user_prefs) = (tomorrow_plans.clone(), user_prefs.clone());
+            tasks.push(tokio::spawn(async move {
+                for _ in 0..20 {
+                    // The same reads as saved_data_snapshot and the submission handlers
+                    for plan in tomorrow_plans.iter() {
+                        assert_eq!(plan.value().plan, format!("plan {}", plan.key()));
+                    }
+                    let prefs: HashMap<String, UserPrefs> =
+                        user_prefs.iter().map(|prefs| (prefs.key().clone(), prefs.value().clone())).collect();
+                    assert!(prefs.values().all(|prefs| prefs.dm_confirmation));
+                    let _ = tomorrow_plans.contains_key("1");
+                    let _ = user_prefs.get("1").is_some_and(|prefs| prefs.dm_confirmation);
+                    tokio::task::yield_now().await;
+                }
+            }));
+        }
+        for task in tasks {
+            task.await.unwrap();
+        }
+
+        assert_eq!(tomorrow_plans.len(), 500);
+        assert!(tomorrow_plans.iter().all(|plan| plan.key().parse::<u32>().unwrap() % 2 == 1));
+        assert_eq!(user_prefs.len(), 1000);
+        assert!(user_prefs.iter().all(|prefs| prefs.dm_confirmation));
+    }
+}